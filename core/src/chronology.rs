@@ -0,0 +1,189 @@
+use super::*;
+use std::{collections::BTreeSet, ops::RangeBounds};
+
+/// what happens to an entity at a moment
+#[derive(
+    Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Event {
+    #[default]
+    Captured,
+    Edited,
+    Viewed,
+}
+
+impl Ephemerality {
+    /// the position before all moments at the time, to range the chronology by time
+    pub fn at(time: SystemTime) -> Self {
+        Self {
+            time,
+            base: MioId::MIN,
+            event: Event::Captured,
+        }
+    }
+}
+
+/// the ephemeralities kept in chronological order, and indexed by their entities
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "Vec<Ephemerality>", into = "Vec<Ephemerality>")]
+pub struct Chronology {
+    moments: BTreeSet<Ephemerality>,
+    bases: HashMap<MioId, BTreeSet<Ephemerality>>,
+}
+
+impl Chronology {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.moments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moments.is_empty()
+    }
+
+    /// insert a moment, wherever it falls in time; returns false if it's already there
+    pub fn insert(&mut self, ephemerality: Ephemerality) -> bool {
+        self.bases
+            .entry(ephemerality.base)
+            .or_default()
+            .insert(ephemerality);
+        self.moments.insert(ephemerality)
+    }
+
+    /// remove all moments of the entity
+    pub fn remove(&mut self, base: &MioId) -> Vec<Ephemerality> {
+        let moments = self.bases.remove(base).unwrap_or_default();
+        for moment in moments.iter() {
+            self.moments.remove(moment);
+        }
+        moments.into_iter().collect()
+    }
+
+    /// all moments of the entity in chronological order
+    pub fn of(&self, base: &MioId) -> impl Iterator<Item = Ephemerality> + '_ {
+        self.bases.get(base).into_iter().flatten().copied()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Ephemerality> + '_ {
+        self.moments.iter().copied()
+    }
+
+    /// the moments within the range; use `Ephemerality::at` to range by time
+    pub fn range(
+        &self,
+        range: impl RangeBounds<Ephemerality>,
+    ) -> impl DoubleEndedIterator<Item = Ephemerality> + '_ {
+        self.moments.range(range).copied()
+    }
+
+    /// the moments within `[since, until)`
+    pub fn between(
+        &self,
+        since: SystemTime,
+        until: SystemTime,
+    ) -> impl DoubleEndedIterator<Item = Ephemerality> + '_ {
+        // an inverted range would panic
+        let until = until.max(since);
+        self.range(Ephemerality::at(since)..Ephemerality::at(until))
+    }
+
+    pub fn first(&self) -> Option<Ephemerality> {
+        self.moments.first().copied()
+    }
+
+    pub fn last(&self) -> Option<Ephemerality> {
+        self.moments.last().copied()
+    }
+}
+
+impl From<Vec<Ephemerality>> for Chronology {
+    fn from(ephemeralities: Vec<Ephemerality>) -> Self {
+        ephemeralities.into_iter().collect()
+    }
+}
+
+impl From<Chronology> for Vec<Ephemerality> {
+    fn from(chronology: Chronology) -> Self {
+        chronology.iter().collect()
+    }
+}
+
+impl FromIterator<Ephemerality> for Chronology {
+    fn from_iter<I: IntoIterator<Item = Ephemerality>>(iter: I) -> Self {
+        let mut chronology = Self::new();
+        for ephemerality in iter {
+            chronology.insert(ephemerality);
+        }
+        chronology
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn moment(secs: u64, ord: usize, event: Event) -> Ephemerality {
+        Ephemerality {
+            time: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+            base: RingId::now(ord).into(),
+            event,
+        }
+    }
+
+    #[test]
+    fn moments_at_the_same_time_are_kept() {
+        let (a, b) = (moment(1, 1, Event::Captured), moment(1, 2, Event::Captured));
+        let edited = Ephemerality {
+            event: Event::Edited,
+            ..a
+        };
+        let mut chronology = Chronology::new();
+        assert!(chronology.insert(a));
+        assert!(chronology.insert(b));
+        assert!(chronology.insert(edited));
+        assert!(!chronology.insert(a));
+        assert_eq!(chronology.len(), 3);
+        assert_eq!(chronology.of(&a.base).collect_vec(), vec![a, edited]);
+        assert_eq!(chronology.remove(&a.base), vec![a, edited]);
+        assert_eq!(chronology.iter().collect_vec(), vec![b]);
+    }
+
+    #[test]
+    fn ranges_by_time() {
+        let moments = [
+            moment(1, 1, Event::Captured),
+            moment(2, 2, Event::Captured),
+            moment(2, 3, Event::Viewed),
+            moment(3, 4, Event::Captured),
+        ];
+        // inserted out of order
+        let chronology = moments.iter().rev().copied().collect::<Chronology>();
+        assert_eq!(chronology.iter().collect_vec(), moments);
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        assert_eq!(
+            chronology.between(at(2), at(3)).collect_vec(),
+            moments[1..3]
+        );
+        assert_eq!(chronology.between(at(3), at(2)).count(), 0);
+        assert_eq!(
+            chronology.range(Ephemerality::at(at(2))..).collect_vec(),
+            moments[1..]
+        );
+        assert_eq!(chronology.first(), Some(moments[0]));
+        assert_eq!(chronology.last(), Some(moments[3]));
+    }
+
+    #[test]
+    fn survives_serialization() {
+        let chronology = [moment(2, 1, Event::Edited), moment(1, 2, Event::Captured)]
+            .into_iter()
+            .collect::<Chronology>();
+        let json = serde_json::to_string(&chronology).unwrap();
+        let parsed: Chronology = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.iter().collect_vec(), chronology.iter().collect_vec());
+    }
+}
@@ -0,0 +1,384 @@
+use super::*;
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+};
+
+/// the progress of a job, reported each time an operation starts or settles
+#[derive(Debug, Clone)]
+pub enum Progress {
+    /// the operation is picked up by a worker
+    Started(OpId),
+    /// the operation has settled, either actualized or failed
+    Settled {
+        operation: OpId,
+        error: Option<String>,
+        /// the number of operations settled so far
+        done: usize,
+        /// the number of operations in the job
+        total: usize,
+    },
+    /// the job is cancelled, and the operations not yet started are dropped
+    Cancelled,
+}
+
+/// actualizes lazy specters on a bounded pool of workers, in the dependency order of the ring;
+/// the jobs spawned upon the same executor run one after another, sharing the workers
+#[derive(Debug)]
+pub struct MioExecutor {
+    /// the maximum number of operations running at the same time
+    pub workers: usize,
+    /// the jobs waiting for their turn, served by a dispatcher started upon the first job
+    queue: Mutex<Option<Sender<Queued>>>,
+}
+
+/// a spawned job waiting for its turn
+#[derive(Debug)]
+struct Queued {
+    mio: Arc<Mio>,
+    ids: HashSet<MioId>,
+    progress: Sender<Progress>,
+    cancel: Arc<AtomicBool>,
+    done: Arc<AtomicBool>,
+    result: Sender<MioForced>,
+}
+
+impl Default for MioExecutor {
+    fn default() -> Self {
+        Self::new(thread::available_parallelism().map_or(1, Into::into))
+    }
+}
+
+impl MioExecutor {
+    pub fn new(workers: usize) -> Self {
+        Self {
+            workers: workers.max(1),
+            queue: Mutex::new(None),
+        }
+    }
+
+    /// the operations needed to actualize the specters, each with the operations it waits for;
+    /// a lazy specter whose operation is gone can never be actualized, nor anything upon it
+    fn plan(
+        mio: &Mio,
+        ids: &HashSet<MioId>,
+    ) -> Result<HashMap<OpId, HashSet<OpId>>, OperationError> {
+        let mut plan = HashMap::new();
+        let mut todo = ids.iter().copied().collect::<Vec<_>>();
+        while let Some(id) = todo.pop() {
+            // concrete specters are always valid, and missing ones are reported later
+            let Some(specter) = mio.ring.specters.get(&id) else {
+                continue;
+            };
+            if specter.fresh(mio) || plan.contains_key(&specter.body.operation) {
+                continue;
+            }
+            let operation = mio
+                .ring
+                .operations
+                .get(&specter.body.operation)
+                .ok_or(OperationError::Unresolved(specter.body.operation))?;
+            let waits = operation
+                .base
+                .iter()
+                .filter_map(|base| mio.ring.specters.get(base))
+                .filter(|base| !base.fresh(mio))
+                .map(|base| base.body.operation)
+                .collect();
+            plan.insert(operation.id, waits);
+            todo.extend(operation.base.iter().copied());
+        }
+        Ok(plan)
+    }
+
+    /// actualize the specters on the current thread, blocking until all have settled
+    pub fn execute(
+        &self,
+        mio: &Mio,
+        ids: HashSet<MioId>,
+        progress: &Sender<Progress>,
+        cancel: &AtomicBool,
+    ) -> MioForced {
+        let mut waits = match Self::plan(mio, &ids) {
+            Ok(plan) => plan,
+            Err(e) => {
+                let e = e.to_string();
+                return ids
+                    .into_iter()
+                    .map(|id| (id, Err(anyhow::anyhow!("{}", e))))
+                    .collect();
+            }
+        };
+        let total = waits.len();
+        let mut settled: HashMap<OpId, Result<(), String>> = HashMap::new();
+
+        let (task_tx, task_rx) = mpsc::channel::<OpId>();
+        let task_rx = Mutex::new(task_rx);
+        let (done_tx, done_rx) = mpsc::channel::<(OpId, Result<(), String>)>();
+        thread::scope(|scope| {
+            for _ in 0..self.workers.min(total) {
+                let (task_rx, done_tx) = (&task_rx, done_tx.clone());
+                scope.spawn(move || loop {
+                    let Ok(Ok(op)) = task_rx.lock().map(|rx| rx.recv()) else {
+                        break;
+                    };
+                    let _ = progress.send(Progress::Started(op));
+                    // a panicking operation must not take the whole job down
+                    let res = catch_unwind(AssertUnwindSafe(|| {
+                        mio.ring
+                            .operations
+                            .get(&op)
+                            .ok_or_else(|| anyhow::anyhow!("operation {} not found", op.stem()))?
+                            .run(mio)
+                    }))
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("operation panicked")))
+                    .map_err(|e| format!("{:#}", e));
+                    if done_tx.send((op, res)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(done_tx);
+
+            let mut running = 0;
+            loop {
+                if !cancel.load(Ordering::Relaxed) {
+                    let ready = waits
+                        .iter()
+                        .filter(|(_, waits)| waits.is_empty())
+                        .map(|(op, _)| *op)
+                        .collect::<Vec<_>>();
+                    for op in ready {
+                        waits.remove(&op);
+                        let _ = task_tx.send(op);
+                        running += 1;
+                    }
+                }
+                if running == 0 {
+                    break;
+                }
+                let Ok((op, res)) = done_rx.recv() else {
+                    break;
+                };
+                running -= 1;
+                // operations waiting for a failed one fail as well, transitively
+                let mut outcomes = vec![(op, res)];
+                while let Some((op, res)) = outcomes.pop() {
+                    let dependents = waits
+                        .iter()
+                        .filter(|(_, waits)| waits.contains(&op))
+                        .map(|(dependent, _)| *dependent)
+                        .collect::<Vec<_>>();
+                    for dependent in dependents {
+                        match &res {
+                            Ok(()) => {
+                                waits.get_mut(&dependent).map(|waits| waits.remove(&op));
+                            }
+                            Err(e) => {
+                                waits.remove(&dependent);
+                                outcomes
+                                    .push((dependent, Err(format!("dependency failed: {}", e))));
+                            }
+                        }
+                    }
+                    let _ = progress.send(Progress::Settled {
+                        operation: op,
+                        error: res.as_ref().err().cloned(),
+                        done: settled.len() + 1,
+                        total,
+                    });
+                    settled.insert(op, res);
+                }
+            }
+            drop(task_tx);
+        });
+
+        if !waits.is_empty() {
+            let _ = progress.send(Progress::Cancelled);
+            for op in waits.into_keys() {
+                settled.insert(op, Err("cancelled".to_string()));
+            }
+        }
+
+        ids.into_iter()
+            .map(|id| {
+                let outcome = mio
+                    .ring
+                    .specters
+                    .get(&id)
+                    .and_then(|specter| settled.get(&specter.body.operation));
+                let res = match outcome {
+                    Some(Ok(())) => Ok(()),
+                    Some(Err(e)) => Err(anyhow::anyhow!("{}", e)),
+                    // not planned, so either already valid or not runnable at all
+                    None => mio.specterish(&id).and_then(|specter| specter.run(mio)),
+                };
+                (id, res)
+            })
+            .collect()
+    }
+
+    /// actualize the specters in the background upon a snapshot of the mio, once the jobs
+    /// spawned before have finished
+    pub fn spawn(&self, mio: Arc<Mio>, ids: HashSet<MioId>) -> MioJob {
+        let (progress_tx, progress) = mpsc::channel();
+        let (result_tx, result) = mpsc::channel();
+        let (cancel, done) = Default::default();
+        let queued = Queued {
+            mio,
+            ids,
+            progress: progress_tx,
+            cancel: Arc::clone(&cancel),
+            done: Arc::clone(&done),
+            result: result_tx,
+        };
+        let mut queue = self.queue.lock().unwrap_or_else(PoisonError::into_inner);
+        let workers = self.workers;
+        let _ = queue
+            .get_or_insert_with(|| {
+                let (queue, jobs) = mpsc::channel::<Queued>();
+                thread::spawn(move || {
+                    let executor = MioExecutor::new(workers);
+                    for job in jobs {
+                        // a panicking job drops its result, which `join` reports
+                        let res = catch_unwind(AssertUnwindSafe(|| {
+                            executor.execute(&job.mio, job.ids, &job.progress, &job.cancel)
+                        }));
+                        if let Ok(res) = res {
+                            let _ = job.result.send(res);
+                        }
+                        job.done.store(true, Ordering::Release);
+                    }
+                });
+                queue
+            })
+            .send(queued);
+        MioJob {
+            progress,
+            cancel,
+            done,
+            result,
+        }
+    }
+}
+
+/// a job running in the background; the results should be recorded with `Mio::record`
+#[derive(Debug)]
+pub struct MioJob {
+    /// the progress reported by the job
+    pub progress: Receiver<Progress>,
+    cancel: Arc<AtomicBool>,
+    done: Arc<AtomicBool>,
+    result: Receiver<MioForced>,
+}
+
+impl MioJob {
+    /// stop starting new operations; the running ones are still waited for
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+
+    /// wait for the job to finish and collect the result of each specter
+    pub fn join(self) -> anyhow::Result<MioForced> {
+        self.result
+            .recv()
+            .map_err(|_| anyhow::anyhow!("job panicked"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a lazy specter and the operation resulting in it, upon the base
+    fn lazy(mio: &mut Mio, base: MioId) -> (OpId, MioId) {
+        let (op, id) = (mio.alloc.allocate().into(), mio.alloc.allocate().into());
+        Specter {
+            id,
+            ext: EntityExt::Png,
+            nonce: Specter::<Lazy>::gen_nouce(),
+            deps: Vec::new(),
+            body: Lazy {
+                operation: op,
+                failure: None,
+            },
+        }
+        .ring(&mut mio.ring)
+        .unwrap();
+        Operation {
+            id: op,
+            kind: OperationKind::Resize,
+            attr: serde_json::Value::Null,
+            base: vec![base],
+            outputs: vec![Output {
+                role: Role::Primary,
+                specter: id,
+            }],
+            dropped: Vec::new(),
+        }
+        .ring(&mut mio.ring)
+        .unwrap();
+        (op, id)
+    }
+
+    #[test]
+    fn plan_follows_lazy_bases() {
+        let (_tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let entity = mio.alloc.allocate().into();
+        let (first, base) = lazy(&mut mio, entity);
+        let (second, id) = lazy(&mut mio, base);
+        let plan = MioExecutor::plan(&mio, &HashSet::from([id])).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan[&second], HashSet::from([first]));
+        assert!(plan[&first].is_empty());
+    }
+
+    #[test]
+    fn plan_rejects_unresolved_bases() {
+        let (_tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let entity = mio.alloc.allocate().into();
+        let (first, base) = lazy(&mut mio, entity);
+        let (_, id) = lazy(&mut mio, base);
+        mio.ring.operations.remove(&first);
+        assert!(matches!(
+            MioExecutor::plan(&mio, &HashSet::from([id])),
+            Err(OperationError::Unresolved(op)) if op == first
+        ));
+        // the job settles with the error instead of waiting forever
+        let (progress, _) = mpsc::channel();
+        let forced = MioExecutor::new(1).execute(
+            &mio,
+            HashSet::from([id]),
+            &progress,
+            &AtomicBool::new(false),
+        );
+        assert!(forced[&id].is_err());
+    }
+
+    #[test]
+    fn spawned_jobs_run_in_turn() {
+        let (_tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let entity = mio.alloc.allocate().into();
+        let (first, id) = lazy(&mut mio, entity);
+        mio.ring.operations.remove(&first);
+        let (mio, executor) = (Arc::new(mio), MioExecutor::new(1));
+        let jobs = (0..3)
+            .map(|_| executor.spawn(Arc::clone(&mio), HashSet::from([id])))
+            .collect::<Vec<_>>();
+        for job in jobs {
+            assert!(job.join().unwrap()[&id].is_err());
+        }
+    }
+}
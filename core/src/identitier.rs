@@ -2,7 +2,7 @@ use serde::{Deserializer, Serializer};
 
 use super::*;
 /// the underlying identifier for all mio ring items including entities, operations and specters
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct RingId {
     /// the millisecond timestamp of the entity's creation
     epoch: u128,
@@ -11,20 +11,26 @@ pub struct RingId {
 }
 
 impl RingId {
+    pub(crate) const MIN: RingId = RingId { epoch: 0, ord: 0 };
+
     pub fn now(ord: usize) -> Self {
         let epoch = SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
-            .as_nanos() as u128;
+            .as_nanos();
         Self { epoch, ord }
     }
+
+    /// the textual form of the identifier, as serialized and as used in file names
+    pub fn stem(&self) -> String {
+        let RingId { epoch, ord } = self;
+        format!("{:x}-{}", epoch, ord)
+    }
 }
 
 impl Serialize for RingId {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let RingId { epoch, ord } = self;
-        let stem = format!("{:x}-{}", epoch, ord);
-        serializer.serialize_str(&stem)
+        serializer.serialize_str(&self.stem())
     }
 }
 
@@ -54,19 +60,40 @@ impl<'de> Deserialize<'de> for RingId {
 }
 
 /// the identifier for all entities and specters
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, From, Into)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, From, Into,
+)]
 pub struct MioId(RingId);
 impl MioId {
+    /// ordered before all other identifiers
+    pub const MIN: MioId = MioId(RingId::MIN);
+
     pub fn stem(&self) -> String {
-        let MioId(id) = self;
-        if let Ok(serde_json::Value::String(id)) = serde_json::to_value(id) {
-            id
-        } else {
-            unreachable!("failed to serialize MioId")
-        }
+        self.0.stem()
     }
 }
 
 /// the identifier for all operations
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, From, Into)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, From, Into,
+)]
 pub struct OpId(RingId);
+impl OpId {
+    pub fn stem(&self) -> String {
+        self.0.stem()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stem_is_serialized() {
+        let id = MioId::from(RingId::now(7));
+        let serialized = serde_json::to_value(id).unwrap();
+        assert_eq!(serialized, serde_json::Value::String(id.stem()));
+        assert_eq!(serde_json::from_value::<MioId>(serialized).unwrap(), id);
+        assert_eq!(OpId::from(RingId::from(id)).stem(), id.stem());
+    }
+}
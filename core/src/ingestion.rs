@@ -0,0 +1,264 @@
+use super::*;
+use std::{io, time::Duration};
+
+/// files modified more recently are likely still being written
+const SETTLE: Duration = Duration::from_secs(2);
+
+/// what becomes of the original files once they're safely in the ring
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Disposal {
+    #[default]
+    Keep,
+    Delete,
+    /// move into the directory
+    Move(PathBuf),
+}
+
+/// a folder whose new files are imported as they appear
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedFolder {
+    pub path: PathBuf,
+    /// whether to watch the subdirectories as well
+    #[serde(default)]
+    pub recursive: bool,
+    /// see `FileImport::include`
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// see `FileImport::exclude`
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub disposal: Disposal,
+}
+
+impl WatchedFolder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            recursive: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            disposal: Disposal::Keep,
+        }
+    }
+
+    /// import the settled files not yet ingested, and dispose of the originals
+    pub fn ingest(&self, mio: &mut Mio) -> anyhow::Result<Vec<MioId>> {
+        let folder = FileImport {
+            paths: vec![self.path.clone()],
+            recursive: self.recursive,
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+        };
+        let context = Metadata::context(Source::Import);
+        let (mut ids, mut disposable) = (Vec::new(), Vec::new());
+        for path in folder.files()? {
+            // the file may vanish between the listing and now
+            let Ok(modified) = fs::metadata(&path).and_then(|metadata| metadata.modified()) else {
+                continue;
+            };
+            if mio.ingested.get(&path) == Some(&modified)
+                || modified.elapsed().is_ok_and(|age| age < SETTLE)
+            {
+                continue;
+            }
+            let imported = match mio.import(&path, &context) {
+                Ok(imported) => imported,
+                // the file may vanish or stay locked; it'll be retried on the next scan
+                Err(e) => {
+                    log::warn!("failed to ingest {}: {:#}", path.display(), e);
+                    continue;
+                }
+            };
+            // remembered first, so that a file failing to be disposed of isn't imported again
+            mio.ingested.insert(path.clone(), modified);
+            // files of unknown types are never in the ring, so they're always kept
+            if imported.is_some() && self.disposal != Disposal::Keep {
+                disposable.push(path);
+            }
+            ids.extend(imported);
+        }
+        mio.trigger(Source::Import, &ids);
+        if disposable.is_empty() {
+            return Ok(ids);
+        }
+        // the originals are only let go once their entities are safely in the index
+        if let Err(e) = mio.flush() {
+            log::warn!("failed to save the index, keeping the originals: {:#}", e);
+            return Ok(ids);
+        }
+        for path in disposable {
+            match self.dispose(&path) {
+                Ok(()) => {
+                    mio.ingested.remove(&path);
+                }
+                Err(e) => log::warn!("failed to dispose of {}: {:#}", path.display(), e),
+            }
+        }
+        Ok(ids)
+    }
+
+    fn dispose(&self, path: &Path) -> anyhow::Result<()> {
+        match &self.disposal {
+            Disposal::Keep => {}
+            Disposal::Delete => fs::remove_file(path)?,
+            Disposal::Move(dir) => {
+                fs::create_dir_all(dir)?;
+                let name = path
+                    .file_name()
+                    .ok_or_else(|| anyhow::anyhow!("no file name in {}", path.display()))?;
+                // never replace what's already in the directory
+                for n in 0..MAX_RENAMES {
+                    match move_to(path, &dir.join(numbered(Path::new(name), n))) {
+                        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                        res => return Ok(res?),
+                    }
+                }
+                anyhow::bail!("no free name for {} in {}", path.display(), dir.display())
+            }
+        }
+        Ok(())
+    }
+}
+
+/// how many numbered names are tried when moving onto taken ones
+const MAX_RENAMES: usize = 1000;
+
+/// the name itself, or numbered like `name (n).ext`
+fn numbered(name: &Path, n: usize) -> PathBuf {
+    if n == 0 {
+        return name.to_path_buf();
+    }
+    let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+    match name.extension() {
+        Some(ext) => format!("{} ({}).{}", stem, n, ext.to_string_lossy()).into(),
+        None => format!("{} ({})", stem, n).into(),
+    }
+}
+
+/// move the file, failing with `AlreadyExists` rather than replacing the target
+fn move_to(path: &Path, target: &Path) -> io::Result<()> {
+    // linking never replaces the target, but fails across file systems
+    match fs::hard_link(path, target) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Err(e),
+        Err(_) => {
+            let mut dst = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(target)?;
+            if let Err(e) = io::copy(&mut fs::File::open(path)?, &mut dst) {
+                let _ = fs::remove_file(target);
+                return Err(e);
+            }
+        }
+    }
+    fs::remove_file(path)
+}
+
+/// the watched folders, stored as `folders.json` in the config dir
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchedFolders {
+    pub folders: Vec<WatchedFolder>,
+}
+
+impl WatchedFolders {
+    pub fn path(dirs: &MioDirs) -> PathBuf {
+        dirs.config_dir.join("folders.json")
+    }
+
+    pub fn read(dirs: &MioDirs) -> anyhow::Result<Self> {
+        let path = Self::path(dirs);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn write(&self, dirs: &MioDirs) -> anyhow::Result<()> {
+        fs::write(Self::path(dirs), serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// ingest all folders, carrying on past the broken ones
+    pub fn ingest(&self, mio: &mut Mio) -> Vec<MioId> {
+        let mut ids = Vec::new();
+        for folder in self.folders.iter() {
+            match folder.ingest(mio) {
+                Ok(imported) => ids.extend(imported),
+                Err(e) => log::warn!("failed to scan {}: {:#}", folder.path.display(), e),
+            }
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbered_names() {
+        assert_eq!(numbered(Path::new("a.png"), 0), PathBuf::from("a.png"));
+        assert_eq!(numbered(Path::new("a.png"), 2), PathBuf::from("a (2).png"));
+        assert_eq!(numbered(Path::new("a"), 1), PathBuf::from("a (1)"));
+    }
+
+    #[test]
+    fn moving_keeps_existing_files() {
+        let (src, dst) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let folder = WatchedFolder {
+            disposal: Disposal::Move(dst.path().to_path_buf()),
+            ..WatchedFolder::new(src.path())
+        };
+        for content in ["first", "second"] {
+            let path = src.path().join("a.txt");
+            fs::write(&path, content).unwrap();
+            folder.dispose(&path).unwrap();
+            assert!(!path.exists());
+        }
+        let moved = |name| fs::read_to_string(dst.path().join(name)).unwrap();
+        assert_eq!(moved("a.txt"), "first");
+        assert_eq!(moved("a (1).txt"), "second");
+    }
+
+    #[test]
+    fn originals_are_kept_unless_the_index_is_saved() {
+        let src = tempfile::tempdir().unwrap();
+        let folder = WatchedFolder {
+            disposal: Disposal::Delete,
+            ..WatchedFolder::new(src.path())
+        };
+        let settled = |name: &str| {
+            let path = src.path().join(name);
+            fs::write(&path, "mio").unwrap();
+            let old = SystemTime::now() - Duration::from_secs(60);
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+            path
+        };
+
+        let (_tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let path = settled("a.txt");
+        // the index can't be written where a directory stands
+        fs::create_dir(&mio.dirs.index_path).unwrap();
+        assert_eq!(folder.ingest(&mut mio).unwrap().len(), 1);
+        assert!(path.exists());
+        // nor is the file imported again
+        assert!(folder.ingest(&mut mio).unwrap().is_empty());
+
+        fs::remove_dir(&mio.dirs.index_path).unwrap();
+        let path = settled("b.txt");
+        assert_eq!(folder.ingest(&mut mio).unwrap().len(), 1);
+        assert!(!path.exists());
+        let saved = fs::read(&mio.dirs.index_path).unwrap();
+        let saved = Cipher::decrypt(saved.as_slice(), &Cipher::index_nonce()).unwrap();
+        let saved = serde_json::from_slice::<Mio>(&saved).unwrap();
+        assert_eq!(saved.ring.entities.len(), 2);
+    }
+}
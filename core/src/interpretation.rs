@@ -1,50 +1,232 @@
 use super::*;
+use std::ops::Bound;
 
 pub struct MioView {
     pub timeline: Vec<Ephemerality>,
     pub ring: MioRing,
+    /// where the next page starts, if there are more moments in the direction of paging
+    pub next: Option<Ephemerality>,
 }
 
 impl MioView {
     pub fn all(mio: &Mio) -> Self {
         MioViewGen::All.interpret(mio).unwrap()
     }
+
+    /// the latest moment of each specter in chronological order, to show each of them once
+    pub fn distinct(&self) -> Vec<Ephemerality> {
+        let mut seen = HashSet::new();
+        let mut distinct = (self.timeline.iter().rev())
+            .filter(|moment| seen.insert(moment.base))
+            .copied()
+            .collect_vec();
+        distinct.reverse();
+        distinct
+    }
+}
+
+/// the length of a bucket of time, in the local timezone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Span {
+    Day,
+    /// from monday to sunday
+    Week,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Older,
+    Newer,
 }
 
 pub enum MioViewGen {
     All,
+    /// `former` moments before the anchor, the anchor itself if present, and `latter` moments after
     Anchor {
         former: usize,
-        anchor: usize,
+        anchor: Ephemerality,
+        latter: usize,
+    },
+    /// the moments within `[since, until)`
+    Range {
+        since: SystemTime,
+        until: SystemTime,
+    },
+    /// the moments of the day or week containing the date
+    Bucket {
+        date: chrono::NaiveDate,
+        span: Span,
+    },
+    /// `former` moments before the time, and `latter` moments since then
+    Around {
+        time: SystemTime,
+        former: usize,
         latter: usize,
     },
+    /// at most `limit` moments next to the cursor, which is the last moment of the previous page;
+    /// without a cursor, start from the latest when going older, or the earliest otherwise
+    Page {
+        cursor: Option<Ephemerality>,
+        limit: usize,
+        direction: Direction,
+    },
+    /// the moments of the inner view, leaving out what was captured automatically
+    Manual(Box<MioViewGen>),
 }
 
-impl Interpretable for MioViewGen {
-    type Mio<'a> = &'a Mio;
-    type Target<'a> = MioView;
-    fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
-        let (low, high) = match self {
-            MioViewGen::All => (0, mio.chronology.len() - 1),
+impl Span {
+    /// the bounds of the bucket containing the date
+    fn bounds(&self, date: chrono::NaiveDate) -> anyhow::Result<(SystemTime, SystemTime)> {
+        use chrono::{Datelike, Days, Local};
+        let (first, days) = match self {
+            Span::Day => (date, 1),
+            Span::Week => (
+                date - Days::new(date.weekday().num_days_from_monday() as u64),
+                7,
+            ),
+        };
+        // a daylight saving transition may skip local midnight, and then the day starts at
+        // the first minute that exists
+        let start = |date: chrono::NaiveDate| -> anyhow::Result<SystemTime> {
+            let midnight = date.and_time(chrono::NaiveTime::MIN);
+            let start = (0..24 * 60)
+                .find_map(|minutes| {
+                    (midnight + chrono::Duration::minutes(minutes))
+                        .and_local_timezone(Local)
+                        .earliest()
+                })
+                .ok_or_else(|| anyhow::anyhow!("no local time on {}", date))?;
+            Ok(start.into())
+        };
+        Ok((start(first)?, start(first + Days::new(days))?))
+    }
+}
+
+impl MioViewGen {
+    /// the moments kept, and where the next page starts
+    fn timeline(
+        self,
+        mio: &Mio,
+        keep: &dyn Fn(&Ephemerality) -> bool,
+    ) -> anyhow::Result<(Vec<Ephemerality>, Option<Ephemerality>)> {
+        let chronology = &mio.chronology;
+        Ok(match self {
+            MioViewGen::All => (chronology.iter().filter(keep).collect_vec(), None),
             MioViewGen::Anchor {
                 former,
                 anchor,
                 latter,
-            } => (
-                (anchor - former).clamp(0, mio.chronology.len() - 1),
-                (anchor + latter).clamp(0, mio.chronology.len() - 1),
+            } => {
+                let mut timeline = chronology
+                    .range(..anchor)
+                    .rev()
+                    .filter(keep)
+                    .take(former)
+                    .collect_vec();
+                timeline.reverse();
+                timeline.extend(chronology.range(anchor..=anchor).filter(keep));
+                timeline.extend(
+                    chronology
+                        .range((Bound::Excluded(anchor), Bound::Unbounded))
+                        .filter(keep)
+                        .take(latter),
+                );
+                (timeline, None)
+            }
+            MioViewGen::Range { since, until } => (
+                chronology.between(since, until).filter(keep).collect_vec(),
+                None,
             ),
-        };
-        let timeline = if high < mio.chronology.len() {
-            mio.chronology[low..=high].iter().cloned().collect_vec()
-        } else {
-            Vec::new()
-        };
+            MioViewGen::Bucket { date, span } => {
+                let (since, until) = span.bounds(date)?;
+                (
+                    chronology.between(since, until).filter(keep).collect_vec(),
+                    None,
+                )
+            }
+            MioViewGen::Around {
+                time,
+                former,
+                latter,
+            } => {
+                let at = Ephemerality::at(time);
+                let mut timeline = chronology
+                    .range(..at)
+                    .rev()
+                    .filter(keep)
+                    .take(former)
+                    .collect_vec();
+                timeline.reverse();
+                timeline.extend(chronology.range(at..).filter(keep).take(latter));
+                (timeline, None)
+            }
+            MioViewGen::Page {
+                cursor,
+                limit,
+                direction: Direction::Older,
+            } => {
+                let until = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+                let mut timeline = chronology
+                    .range((Bound::Unbounded, until))
+                    .rev()
+                    .filter(keep)
+                    .take(limit.saturating_add(1))
+                    .collect_vec();
+                // the extra one only tells whether there are more
+                let next = if timeline.len() > limit {
+                    timeline.pop();
+                    timeline.last().copied()
+                } else {
+                    None
+                };
+                timeline.reverse();
+                (timeline, next)
+            }
+            MioViewGen::Page {
+                cursor,
+                limit,
+                direction: Direction::Newer,
+            } => {
+                let since = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+                let mut timeline = chronology
+                    .range((since, Bound::Unbounded))
+                    .filter(keep)
+                    .take(limit.saturating_add(1))
+                    .collect_vec();
+                let next = if timeline.len() > limit {
+                    timeline.pop();
+                    timeline.last().copied()
+                } else {
+                    None
+                };
+                (timeline, next)
+            }
+            MioViewGen::Manual(inner) => inner.timeline(mio, &|moment| {
+                keep(moment)
+                    && !mio
+                        .ring
+                        .entities
+                        .get(&moment.base)
+                        .is_some_and(|entity| entity.body.metadata.automatic)
+            })?,
+        })
+    }
+}
+
+impl Interpretable for MioViewGen {
+    type Mio<'a> = &'a Mio;
+    type Target<'a> = MioView;
+    fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
+        let (timeline, next) = self.timeline(mio, &|_| true)?;
         let ring = MioRingGen {
             base: timeline.iter().map(|e| e.base).collect::<HashSet<_>>(),
         }
         .interpret(mio)?;
-        Ok(MioView { timeline, ring })
+        Ok(MioView {
+            timeline,
+            ring,
+            next,
+        })
     }
 }
 
@@ -63,7 +245,7 @@ impl Interpretable for MioRingGen {
 
         while required.difference(&done).count() > 0 {
             let todo = required.difference(&done).copied().collect::<HashSet<_>>();
-            let mut add_to_map = |id| -> HashSet<_> {
+            let mut add_to_map = |id: MioId| -> anyhow::Result<HashSet<_>> {
                 done.insert(id);
                 let deps = if let Some(entity) = mio.ring.entities.get(&id) {
                     ring.entities.insert(id, entity.clone());
@@ -72,24 +254,35 @@ impl Interpretable for MioRingGen {
                     ring.specters.insert(id, specter.clone());
                     &specter.deps
                 } else {
-                    unreachable!("specter not found")
+                    anyhow::bail!("specter {} not found", id.stem())
                 };
-                deps.iter().copied().collect()
+                Ok(deps.iter().copied().collect())
             };
             let mut ops = HashSet::new();
             for id in todo {
-                ops.extend(add_to_map(id));
+                ops.extend(add_to_map(id)?);
             }
             for op in ops {
-                let operation = &mio.ring.operations[&op];
+                let operation = mio
+                    .ring
+                    .operations
+                    .get(&op)
+                    .ok_or_else(|| anyhow::anyhow!("operation {} not found", op.stem()))?;
                 ring.operations.insert(op, operation.clone());
                 for id in operation.base.iter().copied() {
                     // don't trace indirect entities
-                    add_to_map(id);
+                    add_to_map(id)?;
                 }
-                required.insert(operation.specter);
+                required.extend(operation.specters());
             }
         }
+        // a stale specter also invalidates everything downstream, which `fresh` follows
+        ring.stale = ring
+            .specters
+            .values()
+            .filter(|specter| specter.exists(&mio.dirs) && !specter.fresh(mio))
+            .map(|specter| specter.id)
+            .collect();
         Ok(ring)
     }
 }
@@ -114,74 +307,200 @@ impl Interpretable for MioInitiate {
     type Mio<'a> = &'a mut Mio;
     type Target<'a> = MioRing;
     fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
-        let operation =
-            {
-                let mut allocator =
-                    mio.specterish(self.base.iter().next().ok_or_else(|| {
-                        anyhow::anyhow!("cannot initiate operation without base")
-                    })?);
-
-                let operation = allocator
-                    .allocate()
-                    .unwrap_or_else(|| mio.alloc.allocate())
-                    .into();
+        // reject invalid operations before the ring is touched
+        let first = *self.base.first().ok_or(OperationError::NoBase)?;
+        let mut src = Vec::new();
+        for base in self.base.iter() {
+            if !mio.ring.contains(base) {
+                return Err(OperationError::Missing(*base).into());
+            }
+            src.push(mio.specterish(base)?.kind());
+        }
+        let layout = mio
+            .registry
+            .find(&self.kind, &src)?
+            .prepare(&self.attr, &self.base, mio)?;
+
+        let operation = {
+            let mut allocator = mio.specterish(&first)?;
+
+            let operation = allocator
+                .allocate()
+                .unwrap_or_else(|| mio.alloc.allocate())
+                .into();
+            let mut outputs = Vec::new();
+            for (role, ext) in layout {
                 let specter = allocator
                     .allocate()
                     .unwrap_or_else(|| mio.alloc.allocate())
                     .into();
-
-                allocator.deps_push(RingId::from(operation))?;
-                allocator.ring(&mut mio.ring)?;
-
-                let ext = self
-                    .kind
-                    .analyze(self.base.iter().map(|base| mio.specterish(base).kind()))
-                    .unwrap()
-                    .ext_hint();
                 Specter {
                     id: specter,
                     ext,
                     nonce: Specter::<Lazy>::gen_nouce(),
                     deps: Vec::new(),
-                    body: Lazy { operation },
+                    body: Lazy {
+                        operation,
+                        failure: None,
+                    },
                 }
                 .ring(&mut mio.ring)?;
-                let operation = Operation {
-                    id: operation,
-                    kind: self.kind,
-                    attr: self.attr,
-                    base: self.base,
-                    specter,
-                };
-                operation.ring_and(&mut mio.ring)?;
-                operation
+                outputs.push(Output { role, specter });
+            }
+
+            allocator.deps_push(RingId::from(operation))?;
+            allocator.ring(&mut mio.ring)?;
+
+            let operation = Operation {
+                id: operation,
+                kind: self.kind,
+                attr: self.attr,
+                base: self.base,
+                outputs,
+                dropped: Vec::new(),
             };
+            operation.ring_and(&mut mio.ring)?;
+            operation
+        };
         // return an incremental ring
         let mut ring = MioRing::new();
-        for id in operation.base.iter().copied() {
-            mio.specterish(&id).ring(&mut ring)?;
+        for id in operation.base.iter() {
+            mio.specterish(id)?.ring(&mut ring)?;
+        }
+        for specter in operation.specters() {
+            ring.specters
+                .insert(specter, mio.ring.specters[&specter].clone());
         }
-        let specter = operation.specter;
-        ring.specters
-            .insert(specter, mio.ring.specters[&specter].clone());
         ring.operations.insert(operation.id, operation);
         Ok(ring)
     }
 }
 
+/// replace the attributes of an existing operation; the outputs are kept but invalidated,
+/// so that they and everything downstream recompute on next access
+pub struct MioAmend {
+    pub operation: OpId,
+    pub attr: serde_json::Value,
+}
+
+impl MioAmend {
+    pub fn new(operation: OpId, attr: impl Operable) -> Self {
+        Self {
+            operation,
+            attr: serde_json::to_value(attr).expect("failed to serialize attribute"),
+        }
+    }
+}
+
+impl Interpretable for MioAmend {
+    type Mio<'a> = &'a mut Mio;
+    type Target<'a> = MioRing;
+    fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
+        let mut operation = mio
+            .ring
+            .operations
+            .get(&self.operation)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("operation not found"))?;
+        // reject invalid attributes before the ring is touched
+        let layout = operation
+            .provider(mio)?
+            .prepare(&self.attr, &operation.base, mio)?;
+        let amended = layout.iter().map(|(role, _)| *role).collect::<HashSet<_>>();
+        let current = operation
+            .outputs
+            .iter()
+            .map(|output| output.role)
+            .collect::<HashSet<_>>();
+        if amended != current {
+            return Err(OperationError::Invalid(
+                "amended attributes must keep the outputs of the operation".to_string(),
+            )
+            .into());
+        }
+
+        for (role, ext) in layout {
+            let Some(id) = operation.output(role) else {
+                continue;
+            };
+            let Some(specter) = mio.ring.specters.get_mut(&id) else {
+                continue;
+            };
+            if specter.exists(&mio.dirs) {
+                specter.remove(&mio.dirs)?;
+            }
+            specter.ext = ext;
+            specter.body.failure = None;
+        }
+        operation.attr = self.attr;
+        operation.ring_and(&mut mio.ring)?;
+        for id in operation.specters() {
+            mio.happen(id, Event::Edited);
+        }
+
+        // return an incremental ring
+        let mut ring = MioRing::new();
+        for id in operation.specters() {
+            if let Some(specter) = mio.ring.specters.get(&id) {
+                ring.specters.insert(id, specter.clone());
+            }
+        }
+        ring.operations.insert(operation.id, operation);
+        Ok(ring)
+    }
+}
+
+/// initiate a frame extraction of the frames selected from a video specter
+#[cfg(feature = "video")]
+pub struct MioFrames {
+    pub base: MioId,
+    pub ext: EntityExt,
+    pub select: FrameSelect,
+}
+
+#[cfg(feature = "video")]
+impl Interpretable for MioFrames {
+    type Mio<'a> = &'a mut Mio;
+    type Target<'a> = MioRing;
+    fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
+        // the video must be actualized before it can be probed
+        let video = mio.specterish(&self.base)?;
+        video.run(mio)?;
+        let src = video.read_as_temp(&mio.dirs)?;
+        let frames = FrameVideo {
+            ext: self.ext,
+            millis: Ffmpeg::select(src.path(), &self.select)?
+                .into_iter()
+                .map(|at| at.as_millis() as u64)
+                .collect(),
+        };
+        MioInitiate::new(frames, vec![self.base]).interpret(mio)
+    }
+}
+
 pub struct MioForce {
     pub ids: HashSet<MioId>,
 }
 
+/// the result of actualizing each specter
+pub type MioForced = HashMap<MioId, anyhow::Result<()>>;
+
 impl Interpretable for MioForce {
-    type Mio<'a> = &'a Mio;
-    type Target<'a> = ();
+    type Mio<'a> = &'a mut Mio;
+    type Target<'a> = MioForced;
 
+    /// actualize all specters, recording failures on them instead of stopping at the first
     fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
+        let mut forced = MioForced::new();
         for id in self.ids {
-            mio.specterish(&id).run(mio)?;
+            let res = mio.specterish(&id).and_then(|specter| specter.run(mio));
+            if let Err(e) = &res {
+                log::warn!("failed to actualize {}: {:#}", id.stem(), e);
+            }
+            mio.record(&id, &res);
+            forced.insert(id, res);
         }
-        Ok(())
+        Ok(forced)
     }
 }
 
@@ -219,24 +538,46 @@ impl Interpretable for MioArchive {
     fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
         let mut archived = MioArchived::default();
         match self {
+            // already archived along another path of the ring
+            MioArchive::Specter(id) if !mio.ring.contains(&id) => {}
+            MioArchive::Operation(id) if !mio.ring.operations.contains_key(&id) => {}
             MioArchive::Specter(id) => {
                 archived += id;
-                let specter = mio.specterish(&id);
+                // an output archived on its own is dropped from its operation, which goes
+                // along with its last output
+                let producer = mio.ring.specters.get(&id).map(|s| s.body.operation);
+                if let Some(operation) = producer.and_then(|op| mio.ring.operations.get_mut(&op)) {
+                    if let Some(at) = operation.outputs.iter().position(|o| o.specter == id) {
+                        let output = operation.outputs.remove(at);
+                        operation.dropped.push(output.role);
+                    }
+                    if operation.outputs.is_empty() {
+                        archived += MioArchive::Operation(operation.id).interpret(mio)?;
+                    }
+                }
+                let specter = mio.specterish(&id)?;
                 specter.ring(&mut mio.archived)?;
                 specter.unring(&mut mio.ring)?;
                 mio.alloc.deallocate(id.into());
-                mio.chronology.retain(|e| e.base != id);
+                mio.chronology.remove(&id);
                 for dep in specter.deps().into_iter().map(Into::into) {
                     archived += MioArchive::Operation(dep).interpret(mio)?;
                 }
             }
             MioArchive::Operation(id) => {
                 archived += id;
-                let operation = mio.ring.operations[&id].clone();
+                let operation = mio
+                    .ring
+                    .operations
+                    .get(&id)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("operation {} not found", id.stem()))?;
                 operation.ring(&mut mio.archived)?;
                 operation.unring(&mut mio.ring)?;
                 mio.alloc.deallocate(id.into());
-                archived += MioArchive::Specter(operation.specter).interpret(mio)?;
+                for specter in operation.specters() {
+                    archived += MioArchive::Specter(specter).interpret(mio)?;
+                }
             }
         }
         Ok(archived)
@@ -251,10 +592,297 @@ impl Interpretable for MioPurge {
 
     fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
         for id in mio.archived.mio_ids() {
-            let mut specter = mio.archived.specterish(id);
+            let mut specter = mio.archived.specterish(id)?;
             specter.remove(&mio.dirs)?;
         }
         mio.archived.clear();
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn archived_outputs_are_dropped_from_their_operation() {
+        let (tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let path = tmp.path().join("mio.png");
+        image::RgbImage::new(2, 2).save(&path).unwrap();
+        let [base] = FileImport::new([&path]).interpret(&mut mio).unwrap()[..] else {
+            panic!("expected one entity")
+        };
+        // a fan-out operation, as if taking frames of a video
+        let op: OpId = mio.alloc.allocate().into();
+        let outputs = (0..3)
+            .map(|n| {
+                let specter = mio.alloc.allocate().into();
+                Specter {
+                    id: specter,
+                    ext: EntityExt::Png,
+                    nonce: Specter::<Lazy>::gen_nouce(),
+                    deps: Vec::new(),
+                    body: Lazy {
+                        operation: op,
+                        failure: None,
+                    },
+                }
+                .ring(&mut mio.ring)
+                .unwrap();
+                Output {
+                    role: Role::Nth(n),
+                    specter,
+                }
+            })
+            .collect_vec();
+        Operation {
+            id: op,
+            kind: OperationKind::Crop,
+            attr: serde_json::Value::Null,
+            base: vec![base],
+            outputs: outputs.clone(),
+            dropped: Vec::new(),
+        }
+        .ring(&mut mio.ring)
+        .unwrap();
+
+        let archived = MioArchive::Specter(outputs[1].specter)
+            .interpret(&mut mio)
+            .unwrap();
+        assert_eq!(archived.mio_id, vec![outputs[1].specter]);
+        let operation = mio.ring.operations[&op].clone();
+        assert_eq!(operation.outputs, vec![outputs[0], outputs[2]]);
+        assert_eq!(operation.dropped, vec![Role::Nth(1)]);
+        // the siblings are still written, and the result of the archived one is let go
+        let res = (0..3).map(|n| (Role::Nth(n), vec![n as u8])).collect();
+        operation.write(&mio, res).unwrap();
+        for output in operation.outputs.iter() {
+            let specter = mio.specterish(&output.specter).unwrap();
+            assert!(specter.exists(&mio.dirs));
+        }
+
+        // the operation goes along with its last output
+        for output in operation.outputs.iter() {
+            MioArchive::Specter(output.specter)
+                .interpret(&mut mio)
+                .unwrap();
+        }
+        assert!(!mio.ring.operations.contains_key(&op));
+        assert!(mio.archived.operations.contains_key(&op));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn amending_invalidates_the_outputs() {
+        let (tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let path = tmp.path().join("mio.png");
+        image::RgbImage::new(2, 2).save(&path).unwrap();
+        let [base] = FileImport::new([&path]).interpret(&mut mio).unwrap()[..] else {
+            panic!("expected one entity")
+        };
+        let convert = |mio: &mut Mio, base, ext| {
+            let attr = ConvertImage { ext, quality: None };
+            let ring = MioInitiate::new(attr, vec![base]).interpret(mio).unwrap();
+            ring.operations.into_values().exactly_one().unwrap()
+        };
+        let operation = convert(&mut mio, base, EntityExt::Jpg);
+        let output = operation.output(Role::Primary).unwrap();
+        let downstream = convert(&mut mio, output, EntityExt::Png);
+        let downstream = downstream.output(Role::Primary).unwrap();
+        let specter = |mio: &Mio, id| mio.ring.specters[&id].clone();
+        specter(&mio, downstream).run(&mio).unwrap();
+        let stale = specter(&mio, output).locate(&mio.dirs);
+        assert!(stale.exists());
+        assert!(specter(&mio, output).fresh(&mio));
+        assert!(specter(&mio, downstream).fresh(&mio));
+
+        let attr = ConvertImage {
+            ext: EntityExt::Webp,
+            quality: None,
+        };
+        MioAmend::new(operation.id, attr)
+            .interpret(&mut mio)
+            .unwrap();
+        assert!(!stale.exists());
+        assert_eq!(specter(&mio, output).ext, EntityExt::Webp);
+        assert!(!specter(&mio, output).fresh(&mio));
+        assert!(!specter(&mio, downstream).fresh(&mio));
+        // and they recompute upon the amended attributes
+        specter(&mio, downstream).run(&mio).unwrap();
+        let webp = specter(&mio, output).read(&mio.dirs).unwrap();
+        assert_eq!(EntityExt::sniff(&webp), Some(EntityExt::Webp));
+        assert!(specter(&mio, downstream).fresh(&mio));
+
+        // invalid attributes leave the outputs untouched
+        let attr = ConvertImage {
+            ext: EntityExt::Txt,
+            quality: None,
+        };
+        assert!(MioAmend::new(operation.id, attr)
+            .interpret(&mut mio)
+            .is_err());
+        assert_eq!(specter(&mio, output).ext, EntityExt::Webp);
+    }
+
+    /// a mio with an entity at each of the times
+    fn timeline(
+        times: impl IntoIterator<Item = SystemTime>,
+    ) -> (tempfile::TempDir, Mio, Vec<Ephemerality>) {
+        let (tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let moments = times
+            .into_iter()
+            .map(|time| {
+                let base = mio.alloc.allocate().into();
+                mio.ring.entities.insert(
+                    base,
+                    Specter {
+                        id: base,
+                        ext: EntityExt::Txt,
+                        nonce: Vec::new(),
+                        deps: Vec::new(),
+                        body: Concrete {
+                            pool: AllocPool::default(),
+                            providence: Providence::Registered,
+                            metadata: Metadata::default(),
+                        },
+                    },
+                );
+                let moment = Ephemerality {
+                    time,
+                    base,
+                    event: Event::Captured,
+                };
+                mio.chronology.insert(moment);
+                moment
+            })
+            .collect_vec();
+        (tmp, mio, moments)
+    }
+
+    fn secs(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs)
+    }
+
+    fn view(mio: &Mio, gen: MioViewGen) -> (Vec<Ephemerality>, Option<Ephemerality>) {
+        let view = gen.interpret(mio).unwrap();
+        let bases = view.timeline.iter().map(|e| e.base).collect::<HashSet<_>>();
+        assert_eq!(
+            view.ring.entities.keys().copied().collect::<HashSet<_>>(),
+            bases
+        );
+        (view.timeline, view.next)
+    }
+
+    #[test]
+    fn windows_stay_within_the_timeline() {
+        let (_tmp, mio, _) = timeline([]);
+        assert_eq!(view(&mio, MioViewGen::All), (vec![], None));
+        let anchored = |anchor, former, latter| MioViewGen::Anchor {
+            former,
+            anchor,
+            latter,
+        };
+        let nowhere = Ephemerality::at(secs(1));
+        assert_eq!(view(&mio, anchored(nowhere, 3, 3)).0, []);
+
+        let (_tmp, mio, m) = timeline([1, 2, 3].map(secs));
+        assert_eq!(view(&mio, MioViewGen::All).0, m);
+        assert_eq!(view(&mio, anchored(m[0], 5, 5)).0, m);
+        assert_eq!(view(&mio, anchored(m[2], usize::MAX, 0)).0, m);
+        assert_eq!(view(&mio, anchored(m[1], 1, 0)).0, m[..2]);
+        assert_eq!(view(&mio, anchored(m[1], 0, 0)).0, m[1..2]);
+        // an anchor that is not in the timeline only has neighbours
+        assert_eq!(
+            view(&mio, anchored(Ephemerality::at(secs(2)), 1, 1)).0,
+            m[..2]
+        );
+
+        let around = |time, former, latter| MioViewGen::Around {
+            time,
+            former,
+            latter,
+        };
+        assert_eq!(view(&mio, around(secs(0), 3, 1)).0, m[..1]);
+        assert_eq!(view(&mio, around(secs(9), 2, 3)).0, m[1..]);
+        assert_eq!(view(&mio, around(secs(2), 1, 1)).0, m[..2]);
+        let range = MioViewGen::Range {
+            since: secs(2),
+            until: secs(3),
+        };
+        assert_eq!(view(&mio, range).0, m[1..2]);
+    }
+
+    #[test]
+    fn buckets_by_local_day_and_week() {
+        use chrono::{Local, NaiveDate, TimeZone};
+        // from wednesday to the monday after
+        let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        let at = |day, hour| -> SystemTime {
+            let time = date(day).and_hms_opt(hour, 0, 0).unwrap();
+            Local.from_local_datetime(&time).earliest().unwrap().into()
+        };
+        let (_tmp, mio, m) = timeline([at(3, 0), at(3, 23), at(4, 12), at(7, 23), at(8, 0)]);
+        let bucket = |day, span| MioViewGen::Bucket {
+            date: date(day),
+            span,
+        };
+        assert_eq!(view(&mio, bucket(3, Span::Day)).0, m[..2]);
+        assert_eq!(view(&mio, bucket(5, Span::Day)).0, []);
+        assert_eq!(view(&mio, bucket(3, Span::Week)).0, m[..4]);
+        assert_eq!(view(&mio, bucket(7, Span::Week)).0, m[..4]);
+        assert_eq!(view(&mio, bucket(8, Span::Week)).0, m[4..]);
+    }
+
+    #[test]
+    fn pages_follow_their_cursor() {
+        let (_tmp, mio, m) = timeline([1, 2, 3, 4, 5].map(secs));
+        let page = |cursor, limit, direction| MioViewGen::Page {
+            cursor,
+            limit,
+            direction,
+        };
+        assert_eq!(
+            view(&mio, page(None, 2, Direction::Older)),
+            (m[3..].to_vec(), Some(m[3]))
+        );
+        assert_eq!(
+            view(&mio, page(Some(m[3]), 2, Direction::Older)),
+            (m[1..3].to_vec(), Some(m[1]))
+        );
+        assert_eq!(
+            view(&mio, page(Some(m[1]), 2, Direction::Older)),
+            (m[..1].to_vec(), None)
+        );
+        assert_eq!(
+            view(&mio, page(None, 2, Direction::Newer)),
+            (m[..2].to_vec(), Some(m[1]))
+        );
+        assert_eq!(
+            view(&mio, page(Some(m[3]), 2, Direction::Newer)),
+            (m[4..].to_vec(), None)
+        );
+        assert_eq!(
+            view(&mio, page(None, usize::MAX, Direction::Older)),
+            (m.clone(), None)
+        );
+        assert_eq!(view(&mio, page(None, 0, Direction::Newer)).0, []);
+    }
+
+    #[test]
+    fn distinct_moments_are_the_latest() {
+        let (_tmp, mut mio, m) = timeline([1, 2, 3].map(secs));
+        let edited = Ephemerality {
+            time: secs(4),
+            event: Event::Edited,
+            ..m[0]
+        };
+        mio.chronology.insert(edited);
+        let view = MioView::all(&mio);
+        assert_eq!(view.timeline.len(), 4);
+        assert_eq!(view.distinct(), [m[1], m[2], edited]);
+    }
+}
@@ -1,13 +1,26 @@
+mod chronology;
+mod executor;
 mod identitier;
+mod ingestion;
 mod interpretation;
+mod lineage;
 mod operation;
 mod persistence;
+mod pipeline;
+mod registry;
 mod security;
 
+pub use chronology::*;
+pub use executor::*;
 pub use identitier::*;
+pub use ingestion::*;
 pub use interpretation::*;
+pub use lineage::*;
 pub use operation::*;
 pub use persistence::*;
+pub use pipeline::*;
+pub use registry::*;
+pub use security::secret;
 
 use aes_gcm::{
     aead::{Aead, Nonce, OsRng},
@@ -25,6 +38,7 @@ use std::{
     io::Write,
     ops::AddAssign,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
     time::SystemTime,
 };
 use tempfile::NamedTempFile;
@@ -43,35 +57,24 @@ pub enum OperationKind {
     Trim,
     Crop,
     Resize,
+    Frame,
     As(EntityKind),
     Summarize,
+    /// operations provided by downstream crates, numbered by the provider
+    Custom(u32),
 }
 
-/// the moment of significant creation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// the moment of significant creation, or of anything else happening to an entity;
+/// moments are ordered by time first, and the rest only tells apart those at the same time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Ephemerality {
     /// the timestamp of the moment
     pub time: SystemTime,
     /// the identifier of the entity that happens at that moment
     pub base: MioId,
-}
-
-impl PartialEq for Ephemerality {
-    fn eq(&self, other: &Self) -> bool {
-        self.time.eq(&other.time)
-    }
-}
-impl Eq for Ephemerality {}
-
-impl PartialOrd for Ephemerality {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.time.partial_cmp(&other.time)
-    }
-}
-impl Ord for Ephemerality {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.time.cmp(&other.time)
-    }
+    /// what happens at that moment
+    #[serde(default)]
+    pub event: Event,
 }
 
 /// all specters have a kind
@@ -185,9 +188,13 @@ impl<Body: Actualizer> Specter<Body> {
 pub enum EntityExt {
     Txt,
     Url,
+    Json,
+    Html,
     Png,
     Jpg,
+    Webp,
     Mp3,
+    Ogg,
     Mp4,
 }
 impl EntityLike for EntityExt {
@@ -195,21 +202,124 @@ impl EntityLike for EntityExt {
         match self {
             EntityExt::Txt => EntityKind::Text,
             EntityExt::Url => EntityKind::Text,
+            EntityExt::Json => EntityKind::Text,
+            EntityExt::Html => EntityKind::Text,
             EntityExt::Png => EntityKind::Image,
             EntityExt::Jpg => EntityKind::Image,
+            EntityExt::Webp => EntityKind::Image,
             EntityExt::Mp3 => EntityKind::Audio,
+            EntityExt::Ogg => EntityKind::Audio,
             EntityExt::Mp4 => EntityKind::Video,
         }
     }
 }
+impl EntityExt {
+    pub const ALL: &'static [EntityExt] = &[
+        EntityExt::Txt,
+        EntityExt::Url,
+        EntityExt::Json,
+        EntityExt::Html,
+        EntityExt::Png,
+        EntityExt::Jpg,
+        EntityExt::Webp,
+        EntityExt::Mp3,
+        EntityExt::Ogg,
+        EntityExt::Mp4,
+    ];
+
+    /// the ext of a file extension, case-insensitively
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "jpeg" => Some(EntityExt::Jpg),
+            "htm" => Some(EntityExt::Html),
+            "text" | "md" => Some(EntityExt::Txt),
+            "opus" => Some(EntityExt::Ogg),
+            extension => Self::ALL
+                .iter()
+                .copied()
+                .find(|ext| ext.to_string() == extension),
+        }
+    }
+
+    /// the iso media brands of plain mp4 video; others such as heic, avif, m4a and mov are not
+    const MP4_BRANDS: &'static [&'static [u8; 4]] = &[
+        b"isom", b"iso2", b"iso4", b"iso5", b"iso6", b"mp41", b"mp42", b"avc1", b"dash", b"mmp4",
+        b"M4V ", b"M4VP", b"MSNV",
+    ];
+
+    /// detect the ext of the content by its magic bytes, falling back to text if it's utf-8
+    /// without control characters other than whitespace
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        let starts = |magic: &[u8]| bytes.starts_with(magic);
+        if starts(b"\x89PNG\r\n\x1a\n") {
+            Some(EntityExt::Png)
+        } else if starts(&[0xFF, 0xD8, 0xFF]) {
+            Some(EntityExt::Jpg)
+        } else if starts(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+            Some(EntityExt::Webp)
+        } else if bytes.get(4..8) == Some(b"ftyp") {
+            let brand = bytes.get(8..12)?;
+            EntityExt::MP4_BRANDS
+                .iter()
+                .any(|b| b.as_slice() == brand)
+                .then_some(EntityExt::Mp4)
+        } else if starts(b"OggS") {
+            Some(EntityExt::Ogg)
+        } else if starts(&[0xFF, 0xFE]) || starts(&[0xFE, 0xFF]) {
+            // utf-16 text is not taken, and its mark would pass for an mpeg frame
+            None
+        } else if starts(b"ID3") || matches!(bytes, [0xFF, b, ..] if EntityExt::mpeg_frame(*b)) {
+            Some(EntityExt::Mp3)
+        } else {
+            let text = std::str::from_utf8(bytes).ok().filter(|text| {
+                !text.contains(|c: char| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+            })?;
+            let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+            let head = text.trim_start().to_ascii_lowercase();
+            if head.starts_with("<!doctype html") || head.starts_with("<html") {
+                Some(EntityExt::Html)
+            } else if (head.starts_with('{') || head.starts_with('['))
+                && serde_json::from_str::<serde::de::IgnoredAny>(text).is_ok()
+            {
+                Some(EntityExt::Json)
+            } else {
+                Some(EntityExt::Txt)
+            }
+        }
+    }
+
+    /// whether the second byte of a frame sync holds a version and a layer that aren't reserved
+    fn mpeg_frame(b: u8) -> bool {
+        b & 0xE0 == 0xE0 && (b >> 3) & 0b11 != 0b01 && (b >> 1) & 0b11 != 0
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            EntityExt::Txt => "text/plain",
+            EntityExt::Url => "text/uri-list",
+            EntityExt::Json => "application/json",
+            EntityExt::Html => "text/html",
+            EntityExt::Png => "image/png",
+            EntityExt::Jpg => "image/jpeg",
+            EntityExt::Webp => "image/webp",
+            EntityExt::Mp3 => "audio/mpeg",
+            EntityExt::Ogg => "audio/ogg",
+            EntityExt::Mp4 => "video/mp4",
+        }
+    }
+}
 impl Display for EntityExt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             EntityExt::Txt => write!(f, "txt"),
             EntityExt::Url => write!(f, "url"),
+            EntityExt::Json => write!(f, "json"),
+            EntityExt::Html => write!(f, "html"),
             EntityExt::Png => write!(f, "png"),
             EntityExt::Jpg => write!(f, "jpg"),
+            EntityExt::Webp => write!(f, "webp"),
             EntityExt::Mp3 => write!(f, "mp3"),
+            EntityExt::Ogg => write!(f, "ogg"),
             EntityExt::Mp4 => write!(f, "mp4"),
         }
     }
@@ -228,6 +338,9 @@ pub struct Concrete {
     pub pool: AllocPool,
     /// where the entity comes from, and how will it be treated
     pub providence: Providence,
+    /// what is known about the capture of the entity
+    #[serde(default)]
+    pub metadata: Metadata,
 }
 #[typetag::serde]
 impl Actualizer for Concrete {}
@@ -240,7 +353,7 @@ impl Locatable for Specter<Concrete> {
         self.ext
     }
     fn nonce(&self) -> &Nonce<Aes256Gcm> {
-        Nonce::<Aes256Gcm>::from_slice(&self.nonce.as_slice())
+        Nonce::<Aes256Gcm>::from_slice(self.nonce.as_slice())
     }
 }
 impl Ringable for Specter<Concrete> {
@@ -300,10 +413,28 @@ pub enum Providence {
 pub struct Lazy {
     /// the identifier of the operation that results in the specter
     pub operation: OpId,
+    /// the last failure to actualize the specter, if it hasn't succeeded since
+    #[serde(default)]
+    pub failure: Option<Failure>,
+}
+
+/// the record of failed attempts to actualize a lazy specter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Failure {
+    /// the error message of the last attempt
+    pub message: String,
+    /// the number of failed attempts in a row
+    pub attempts: usize,
+    /// when the last attempt failed
+    pub time: SystemTime,
 }
 #[typetag::serde]
 impl Actualizer for Lazy {}
 
+/// the hash of everything an actualized lazy specter is computed from
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint(pub String);
+
 impl Locatable for Specter<Lazy> {
     fn locate(&self, dirs: &MioDirs) -> PathBuf {
         dirs.cache_dir
@@ -313,7 +444,13 @@ impl Locatable for Specter<Lazy> {
         self.ext
     }
     fn nonce(&self) -> &Nonce<Aes256Gcm> {
-        Nonce::<Aes256Gcm>::from_slice(&self.nonce.as_slice())
+        Nonce::<Aes256Gcm>::from_slice(self.nonce.as_slice())
+    }
+    fn remove(&mut self, dirs: &MioDirs) -> anyhow::Result<()> {
+        fs::remove_file(self.locate(dirs))?;
+        // the fingerprint is meaningless without the specter
+        let _ = fs::remove_file(self.fingerprint_path(dirs));
+        Ok(())
     }
 }
 impl Ringable for Specter<Lazy> {
@@ -342,13 +479,23 @@ impl Allocable for Specter<Lazy> {
     }
 }
 impl Actualizable for Specter<Lazy> {
-    /// if the specter exists, do nothing; otherwise, run the operation
+    /// if the specter exists upon the current inputs, do nothing; otherwise, run the operation
     fn run(&self, mio: &Mio) -> anyhow::Result<()> {
-        if self.exists(&mio.dirs) {
+        let operation = mio
+            .ring
+            .operations
+            .get(&self.body.operation)
+            .ok_or_else(|| anyhow::anyhow!("operation of specter {} not found", self.id.stem()))?;
+        // bring the bases up to date before comparing against them
+        for base in operation.base.iter() {
+            mio.specterish(base)?.run(mio)?;
+        }
+        if self.exists(&mio.dirs)
+            && self.fingerprint(&mio.dirs) == Some(operation.fingerprint(mio)?)
+        {
             return Ok(());
-        } else {
-            mio.ring.operations[&self.body.operation].run(mio)
         }
+        operation.run(mio)
     }
 }
 impl RingableAnd for Specter<Lazy> {
@@ -361,10 +508,41 @@ impl RingableAnd for Specter<Lazy> {
     }
 }
 impl Specter<Lazy> {
+    pub fn fingerprint_path(&self, dirs: &MioDirs) -> PathBuf {
+        dirs.cache_dir
+            .join(format!("{}.{}", self.id.stem(), "fingerprint"))
+    }
+
+    /// the fingerprint of the inputs when the specter was last actualized
+    pub fn fingerprint(&self, dirs: &MioDirs) -> Option<Fingerprint> {
+        fs::read_to_string(self.fingerprint_path(dirs))
+            .ok()
+            .map(Fingerprint)
+    }
+
+    /// whether the specter is actualized upon the current inputs, all the way upstream;
+    /// unlike `run`, nothing is actualized on the way
+    pub fn fresh(&self, mio: &Mio) -> bool {
+        let Some(operation) = mio.ring.operations.get(&self.body.operation) else {
+            return false;
+        };
+        self.exists(&mio.dirs)
+            && operation.base.iter().all(|base| {
+                mio.ring
+                    .specters
+                    .get(base)
+                    .is_none_or(|base| base.fresh(mio))
+            })
+            && operation
+                .fingerprint(mio)
+                .is_ok_and(|fingerprint| self.fingerprint(&mio.dirs) == Some(fingerprint))
+    }
+
     /// elevate an actualized lazy specter to a concrete specter
     pub fn elevate(self, dirs: &MioDirs) -> anyhow::Result<Specter<Concrete>> {
         if self.exists(dirs) {
             let old_path = self.locate(dirs);
+            let fingerprint_path = self.fingerprint_path(dirs);
             let specter = Specter {
                 id: self.id,
                 ext: self.ext,
@@ -373,11 +551,13 @@ impl Specter<Lazy> {
                 body: Concrete {
                     pool: AllocPool::default(),
                     providence: Providence::Induced,
+                    metadata: Metadata::default(),
                 },
             };
             // move the file from cache to data
             fs::copy(old_path.as_path(), specter.locate(dirs))?;
             fs::remove_file(old_path.as_path())?;
+            let _ = fs::remove_file(fingerprint_path);
             Ok(specter)
         } else {
             anyhow::bail!("specter not actualized")
@@ -385,8 +565,29 @@ impl Specter<Lazy> {
     }
 }
 
+/// the role a resulting specter plays in its operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    /// the main result of the operation
+    Primary,
+    /// the n-th of a sequence of results, e.g. the frames of a video
+    Nth(usize),
+    /// the structured data accompanying the primary result
+    Sidecar,
+}
+
+/// a resulting specter of an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Output {
+    /// the role of the specter in the operation
+    pub role: Role,
+    /// the identifier of the resulting specter
+    pub specter: MioId,
+}
+
 /// the operation that can be done upon specters
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "OperationRepr")]
 pub struct Operation {
     /// the identifier of the operation itself
     pub id: OpId,
@@ -396,8 +597,58 @@ pub struct Operation {
     pub attr: serde_json::Value,
     /// the identifiers of the specters that the operation is based on
     pub base: Vec<MioId>,
-    /// the identifier of the resulting specter
-    pub specter: MioId,
+    /// the resulting specters, each with its role
+    pub outputs: Vec<Output>,
+    /// the roles whose specters were archived on their own, so that their results are let go
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped: Vec<Role>,
+}
+
+/// an operation as it may be found in the index, including those from before there were roles
+#[derive(Deserialize)]
+struct OperationRepr {
+    id: OpId,
+    kind: OperationKind,
+    attr: serde_json::Value,
+    base: Vec<MioId>,
+    #[serde(default)]
+    outputs: Vec<Output>,
+    #[serde(default)]
+    dropped: Vec<Role>,
+    /// the single resulting specter of earlier indices
+    #[serde(default)]
+    specter: Option<MioId>,
+}
+
+impl From<OperationRepr> for Operation {
+    fn from(repr: OperationRepr) -> Self {
+        let OperationRepr {
+            id,
+            kind,
+            attr,
+            base,
+            mut outputs,
+            dropped,
+            specter,
+        } = repr;
+        if let Some(specter) = specter {
+            outputs.insert(
+                0,
+                Output {
+                    role: Role::Primary,
+                    specter,
+                },
+            );
+        }
+        Self {
+            id,
+            kind,
+            attr,
+            base,
+            outputs,
+            dropped,
+        }
+    }
 }
 
 impl Ringable for Operation {
@@ -431,21 +682,94 @@ impl RingableAnd for Operation {
     }
 }
 
+/// where the registered entities come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Source {
+    ScreenShot,
+    Clipboard,
+    Import,
+    VoiceMemo,
+    ScreenRecording,
+}
+
+impl Source {
+    /// whether the focused window tells where the entity comes from
+    pub fn focused(&self) -> bool {
+        matches!(self, Source::ScreenShot | Source::Clipboard)
+    }
+}
+
+/// the geometry of a display in global coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Monitor {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale: f32,
+    pub primary: bool,
+}
+
+/// what is known about the capture of an entity; anything unavailable is left out
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub source: Option<Source>,
+    /// the kind of the content, for sources that offer several
+    pub content: Option<EntityKind>,
+    /// the mime type of the content as originally offered by the source
+    #[serde(default)]
+    pub mime: Option<String>,
+    /// the display captured
+    pub display: Option<Monitor>,
+    /// the input device recorded
+    #[serde(default)]
+    pub device: Option<String>,
+    /// the focused application
+    pub application: Option<String>,
+    /// the title of the focused window
+    pub window: Option<String>,
+    pub hostname: Option<String>,
+    /// the file it's imported from
+    #[serde(default)]
+    pub origin: Option<PathBuf>,
+    /// when it originally came to be, if earlier than the registration
+    #[serde(default)]
+    pub modified: Option<SystemTime>,
+    /// whether it's captured by itself rather than by the user, e.g. on a schedule
+    #[serde(default)]
+    pub automatic: bool,
+}
+
 /// the persistable can be persisted into the file system
 pub trait Persistable {
-    fn persist(&self) -> anyhow::Result<Vec<(NamedTempFile, EntityExt)>>;
+    fn source(&self) -> Source;
+    /// the persisted files, each with the metadata specific to it;
+    /// the common metadata is filled in upon registration
+    fn persist(&self) -> anyhow::Result<Vec<(NamedTempFile, EntityExt, Metadata)>>;
 }
 
 /// the operable can be done upon specters
 pub trait Operable: Sized + Serialize + for<'de> Deserialize<'de> {
-    /// validate the kind and existence of source and return it if valid
-    fn prepare(op: &Operation) -> anyhow::Result<Self> {
-        let value = serde_json::from_value(op.attr.clone())?;
+    /// deserialize the attributes and validate them against the base specters
+    fn prepare(
+        attr: &serde_json::Value,
+        base: &[MioId],
+        mio: &Mio,
+    ) -> Result<Self, OperationError> {
+        let value: Self = serde_json::from_value(attr.clone())?;
+        value.validate(base, mio)?;
         Ok(value)
     }
+    /// validate the attributes against the base specters
+    fn validate(&self, _base: &[MioId], _mio: &Mio) -> Result<(), OperationError> {
+        Ok(())
+    }
     fn kind(&self) -> OperationKind;
-    /// apply the operator
-    fn execute<'a>(self, src: NamedTempFile) -> anyhow::Result<Vec<u8>>;
+    /// the roles and extensions of the resulting specters
+    fn outputs(&self) -> Vec<(Role, EntityExt)>;
+    /// apply the operator, yielding the content of each resulting specter by its role
+    fn execute(self, src: NamedTempFile) -> anyhow::Result<Vec<(Role, Vec<u8>)>>;
 }
 
 pub trait Interpretable {
@@ -483,6 +807,25 @@ impl MioDirs {
     }
 }
 
+#[cfg(test)]
+impl MioDirs {
+    /// the dirs under a temporary directory, which is removed once dropped
+    pub(crate) fn temp() -> (tempfile::TempDir, Self) {
+        let tmp = tempfile::tempdir().unwrap();
+        let data_dir = tmp.path().join("data");
+        let dirs = MioDirs {
+            config_dir: tmp.path().join("config"),
+            cache_dir: tmp.path().join("cache"),
+            index_path: data_dir.join("index.bin"),
+            data_dir,
+        };
+        for dir in [&dirs.config_dir, &dirs.cache_dir, &dirs.data_dir] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        (tmp, dirs)
+    }
+}
+
 impl Default for MioDirs {
     fn default() -> Self {
         Self::new()
@@ -540,9 +883,8 @@ pub struct AllocPool {
 
 impl AllocPool {
     fn _allocate(&mut self) -> Option<usize> {
-        self.inner.iter().next().copied().map(|ord| {
-            self.inner.remove(&ord);
-            ord
+        self.inner.iter().next().copied().inspect(|ord| {
+            self.inner.remove(ord);
         })
     }
     pub fn allocate(&mut self) -> Option<RingId> {
@@ -571,6 +913,9 @@ pub struct MioRing {
     pub operations: HashMap<OpId, Operation>,
     /// the specters within the mio ring
     pub specters: HashMap<MioId, Specter<Lazy>>,
+    /// the actualized specters whose inputs have changed since, as found by `MioRingGen`
+    #[serde(skip)]
+    pub stale: HashSet<MioId>,
 }
 
 impl MioRing {
@@ -578,20 +923,31 @@ impl MioRing {
         Self::default()
     }
 
-    pub fn specterish(&self, id: &MioId) -> Box<dyn Specterish> {
+    pub fn specterish(&self, id: &MioId) -> anyhow::Result<Box<dyn Specterish>> {
         if let Some(entity) = self.entities.get(id) {
-            Box::new(entity.clone())
+            Ok(Box::new(entity.clone()))
         } else if let Some(specter) = self.specters.get(id) {
-            Box::new(specter.clone())
+            Ok(Box::new(specter.clone()))
         } else {
-            unreachable!("specter not found")
+            anyhow::bail!("specter {} not found", id.stem())
         }
     }
 
+    pub fn contains(&self, id: &MioId) -> bool {
+        self.entities.contains_key(id) || self.specters.contains_key(id)
+    }
+
     pub fn mio_ids(&self) -> impl Iterator<Item = &MioId> {
         self.entities.keys().chain(self.specters.keys())
     }
 
+    /// absorb all items of another ring, overwriting the existing ones
+    pub fn merge(&mut self, other: MioRing) {
+        self.entities.extend(other.entities);
+        self.operations.extend(other.operations);
+        self.specters.extend(other.specters);
+    }
+
     pub fn delete(&mut self, deleted: MioArchived) {
         for id in deleted.mio_id {
             self.entities.remove(&id);
@@ -617,16 +973,34 @@ pub struct Mio {
     /// the path manager
     #[serde(skip)]
     pub dirs: MioDirs,
+    /// the registry of operations
+    #[serde(skip)]
+    pub registry: OperationRegistry,
+    /// the user-defined pipelines
+    #[serde(skip)]
+    pub pipelines: Pipelines,
+    /// the executor shared by all background jobs
+    #[serde(skip)]
+    pub executor: Arc<MioExecutor>,
+    /// the content hashes of the bases, shared with the snapshots of background jobs
+    #[serde(skip)]
+    pub digests: Arc<Digests>,
+    /// the background jobs yet to be settled
+    #[serde(skip)]
+    pub jobs: Arc<Mutex<Vec<MioJob>>>,
     /// the allocator of `MioId`s
     pub alloc: Alloc,
     /// the null entity
     pub null: MioId,
     /// the ephemerality in chronological order
-    pub chronology: Vec<Ephemerality>,
+    pub chronology: Chronology,
     /// the mio ring
     pub ring: MioRing,
     /// the ring of the archived, storing the yet-to-be-garbage-collected
     pub archived: MioRing,
+    /// the files kept in watched folders that are already ingested, with their modification time
+    #[serde(default)]
+    pub ingested: HashMap<PathBuf, SystemTime>,
 }
 
 impl Mio {
@@ -634,12 +1008,18 @@ impl Mio {
         let mut alloc = Alloc::default();
         let null = alloc.allocate().into();
         Self {
+            pipelines: Pipelines::read_or_default(&dirs),
             dirs,
+            registry: OperationRegistry::default(),
+            executor: Default::default(),
+            digests: Default::default(),
+            jobs: Default::default(),
             alloc,
             null,
-            chronology: Vec::new(),
+            chronology: Chronology::new(),
             ring: MioRing::new(),
             archived: MioRing::new(),
+            ingested: HashMap::new(),
         }
     }
 
@@ -648,15 +1028,16 @@ impl Mio {
         if let Ok(mio_content) = fs::read(&dirs.index_path) {
             if let Ok(mio_content) = Cipher::decrypt(mio_content.as_slice(), &Cipher::index_nonce())
             {
-                if let Ok(mio) = serde_json::from_slice(&mio_content) {
+                if let Ok(mut mio) = serde_json::from_slice::<Self>(&mio_content) {
                     // all success
+                    mio.pipelines = Pipelines::read_or_default(&mio.dirs);
                     return mio;
                 }
             }
             log::warn!("can't parse mio index, backup current file");
             fs::rename(
                 &dirs.index_path,
-                &dirs.data_dir.join(format!(
+                dirs.data_dir.join(format!(
                     "index.bin.{}.bak",
                     SystemTime::now()
                         .duration_since(std::time::UNIX_EPOCH)
@@ -679,9 +1060,67 @@ impl Mio {
         Ok(())
     }
 
-    pub fn specterish(&self, id: &MioId) -> Box<dyn Specterish> {
+    pub fn specterish(&self, id: &MioId) -> anyhow::Result<Box<dyn Specterish>> {
         self.ring.specterish(id)
     }
+
+    /// note down that something happens to the entity or specter just now, e.g. that it's
+    /// viewed; returns false if the very moment is already noted
+    pub fn happen(&mut self, base: MioId, event: Event) -> bool {
+        self.chronology.insert(Ephemerality {
+            time: SystemTime::now(),
+            base,
+            event,
+        })
+    }
+
+    /// remember the result of an attempt to actualize the lazy specter upon every specter
+    /// the attempt concerned: all outputs of its operation, and upon failure the lazy bases
+    /// that were left unactualized
+    pub fn record(&mut self, id: &MioId, res: &anyhow::Result<()>) {
+        let mut todo = vec![*id];
+        let mut done = HashSet::new();
+        while let Some(id) = todo.pop() {
+            let Some(operation) = self
+                .ring
+                .specters
+                .get(&id)
+                .and_then(|specter| self.ring.operations.get(&specter.body.operation))
+            else {
+                continue;
+            };
+            let outputs = operation.specters().collect::<Vec<_>>();
+            for base in operation.base.iter() {
+                // a successful run actualized every base along the way
+                let failed = match self.ring.specters.get(base) {
+                    Some(specter) => res.is_ok() || !specter.exists(&self.dirs),
+                    None => false,
+                };
+                if failed && !done.contains(base) {
+                    todo.push(*base);
+                }
+            }
+            for output in outputs {
+                if !done.insert(output) {
+                    continue;
+                }
+                let Some(specter) = self.ring.specters.get_mut(&output) else {
+                    continue;
+                };
+                match res {
+                    Ok(()) => specter.body.failure = None,
+                    Err(e) => {
+                        let attempts = specter.body.failure.as_ref().map_or(0, |f| f.attempts);
+                        specter.body.failure = Some(Failure {
+                            message: format!("{:#}", e),
+                            attempts: attempts + 1,
+                            time: SystemTime::now(),
+                        });
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl Default for Mio {
@@ -689,3 +1128,93 @@ impl Default for Mio {
         Self::with_dirs(MioDirs::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff() {
+        let sniff = |bytes: &[u8]| EntityExt::sniff(bytes);
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n...."), Some(EntityExt::Png));
+        assert_eq!(sniff(&[0xFF, 0xD8, 0xFF, 0xE0]), Some(EntityExt::Jpg));
+        assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some(EntityExt::Webp));
+        assert_eq!(sniff(b"\0\0\0\x20ftypisom\0\0\x02\0"), Some(EntityExt::Mp4));
+        assert_eq!(sniff(b"\0\0\0\x18ftypmp42\0\0\0\0"), Some(EntityExt::Mp4));
+        for brand in [b"heic", b"avif", b"M4A ", b"qt  "] {
+            let mut bytes = b"\0\0\0\x18ftyp".to_vec();
+            bytes.extend_from_slice(brand);
+            assert_eq!(sniff(&bytes), None);
+        }
+        assert_eq!(sniff(b"\0\0\0\x18ftyp"), None);
+        assert_eq!(sniff(b"ID3\x04\0"), Some(EntityExt::Mp3));
+        assert_eq!(sniff(&[0xFF, 0xFB, 0x90, 0x00]), Some(EntityExt::Mp3));
+        // reserved versions and layers
+        assert_eq!(sniff(&[0xFF, 0xEB, 0x90, 0x00]), None);
+        assert_eq!(sniff(&[0xFF, 0xF9, 0x90, 0x00]), None);
+        // utf-16 byte order marks
+        assert_eq!(sniff(&[0xFF, 0xFE, b'm', 0x00]), None);
+        assert_eq!(sniff(&[0xFE, 0xFF, 0x00, b'm']), None);
+        assert_eq!(sniff(b"\xEF\xBB\xBF<html></html>"), Some(EntityExt::Html));
+        assert_eq!(sniff(b"\xEF\xBB\xBF[1, 2]"), Some(EntityExt::Json));
+        assert_eq!(sniff(b"OggS\0\x02"), Some(EntityExt::Ogg));
+        assert_eq!(sniff(b"<!DOCTYPE html><p>mio</p>"), Some(EntityExt::Html));
+        assert_eq!(sniff(br#"{ "mio": [1, 2] }"#), Some(EntityExt::Json));
+        assert_eq!(sniff(b"{ not json"), Some(EntityExt::Txt));
+        assert_eq!(sniff(b"mio\r\n\tring\n"), Some(EntityExt::Txt));
+        assert_eq!(sniff(b"mio\0ring"), None);
+        assert_eq!(sniff(b"mio\x1bring"), None);
+        assert_eq!(sniff(&[0xC3, 0x28]), None);
+    }
+
+    #[test]
+    fn baseline_index_is_read() {
+        // as flushed before operations could result in several specters
+        let index = r#"{
+            "alloc": { "ord": 4, "hill": { "inner": [] } },
+            "null": "1-0",
+            "chronology": [
+                { "time": { "secs_since_epoch": 1, "nanos_since_epoch": 0 }, "base": "1-1" }
+            ],
+            "ring": {
+                "entities": {
+                    "1-1": {
+                        "id": "1-1", "ext": "Png", "nonce": [], "deps": ["1-2"],
+                        "body": { "pool": { "inner": [] }, "providence": "Registered" }
+                    }
+                },
+                "operations": {
+                    "1-2": {
+                        "id": "1-2", "kind": "Resize", "attr": { "width": 2, "height": 2 },
+                        "base": ["1-1"], "specter": "1-3"
+                    }
+                },
+                "specters": {
+                    "1-3": {
+                        "id": "1-3", "ext": "Png", "nonce": [], "deps": [],
+                        "body": { "operation": "1-2" }
+                    }
+                }
+            },
+            "archived": { "entities": {}, "operations": {}, "specters": {} }
+        }"#;
+        let mio: Mio = serde_json::from_str(index).unwrap();
+        let id = |stem: &str| serde_json::from_value::<RingId>(stem.into()).unwrap();
+        let (entity, op, specter) = (id("1-1").into(), OpId::from(id("1-2")), id("1-3").into());
+        let outputs = |mio: &Mio| mio.ring.operations[&op].outputs.clone();
+        let primary = vec![Output {
+            role: Role::Primary,
+            specter,
+        }];
+        assert_eq!(outputs(&mio), primary);
+        assert_eq!(mio.chronology.of(&entity).count(), 1);
+        assert_eq!(mio.ring.specters[&specter].body.operation, op);
+
+        // and flushed again in the current shape
+        let flushed = serde_json::to_value(&mio).unwrap();
+        let operation = &flushed["ring"]["operations"][op.stem()];
+        assert!(operation.get("specter").is_none());
+        let mio: Mio = serde_json::from_value(flushed).unwrap();
+        assert_eq!(outputs(&mio), primary);
+    }
+}
@@ -0,0 +1,475 @@
+use super::*;
+use std::collections::{BTreeSet, VecDeque};
+
+/// a node of the lineage graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Node {
+    /// an entity or a lazy specter
+    Specter(MioId),
+    Operation(OpId),
+}
+
+impl Node {
+    pub fn stem(&self) -> String {
+        match self {
+            Node::Specter(id) => id.stem(),
+            Node::Operation(id) => id.stem(),
+        }
+    }
+}
+
+/// the provenance of a ring as a dag, where specters lead to the operations based on them
+/// and operations lead to their resulting specters
+pub struct Lineage<'a> {
+    ring: &'a MioRing,
+    parents: HashMap<Node, Vec<Node>>,
+    children: HashMap<Node, Vec<Node>>,
+}
+
+impl MioRing {
+    pub fn lineage(&self) -> Lineage<'_> {
+        Lineage::new(self)
+    }
+}
+
+impl<'a> Lineage<'a> {
+    pub fn new(ring: &'a MioRing) -> Self {
+        let mut parents: HashMap<Node, Vec<Node>> = HashMap::new();
+        let mut children: HashMap<Node, Vec<Node>> = HashMap::new();
+        let mut link = |from: Node, to: Node| {
+            children.entry(from).or_default().push(to);
+            parents.entry(to).or_default().push(from);
+        };
+        // only the operations know all their bases; the deps of specters are incomplete
+        for operation in ring.operations.values() {
+            let op = Node::Operation(operation.id);
+            for base in operation.base.iter().filter(|base| ring.contains(base)) {
+                link(Node::Specter(*base), op);
+            }
+            for specter in operation
+                .specters()
+                .filter(|specter| ring.contains(specter))
+            {
+                link(op, Node::Specter(specter));
+            }
+        }
+        for edges in parents.values_mut().chain(children.values_mut()) {
+            edges.sort();
+            edges.dedup();
+        }
+        Self {
+            ring,
+            parents,
+            children,
+        }
+    }
+
+    /// all nodes in the ring, in a stable order
+    pub fn nodes(&self) -> Vec<Node> {
+        let mut nodes = self
+            .ring
+            .mio_ids()
+            .copied()
+            .map(Node::Specter)
+            .chain(self.ring.operations.keys().copied().map(Node::Operation))
+            .collect_vec();
+        nodes.sort();
+        nodes
+    }
+
+    pub fn parents(&self, node: Node) -> &[Node] {
+        self.parents.get(&node).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn children(&self, node: Node) -> &[Node] {
+        self.children.get(&node).map_or(&[], Vec::as_slice)
+    }
+
+    fn reach(&self, node: Node, edges: &HashMap<Node, Vec<Node>>) -> BTreeSet<Node> {
+        let mut reached = BTreeSet::new();
+        let mut todo = vec![node];
+        while let Some(node) = todo.pop() {
+            for next in edges.get(&node).into_iter().flatten() {
+                if reached.insert(*next) {
+                    todo.push(*next);
+                }
+            }
+        }
+        reached
+    }
+
+    /// everything the node is derived from
+    pub fn ancestors(&self, node: Node) -> BTreeSet<Node> {
+        self.reach(node, &self.parents)
+    }
+
+    /// everything derived from the node
+    pub fn descendants(&self, node: Node) -> BTreeSet<Node> {
+        self.reach(node, &self.children)
+    }
+
+    /// the nodes derived from nothing, i.e. the registered entities
+    pub fn roots(&self) -> Vec<Node> {
+        self.nodes()
+            .into_iter()
+            .filter(|node| self.parents(*node).is_empty())
+            .collect()
+    }
+
+    /// the shortest path of derivation from one node to another, both inclusive
+    pub fn path(&self, from: Node, to: Node) -> Option<Vec<Node>> {
+        let mut prev = HashMap::new();
+        let mut todo = VecDeque::from([from]);
+        while let Some(node) = todo.pop_front() {
+            if node == to {
+                let mut path = vec![to];
+                while let Some(node) = prev.get(path.last()?) {
+                    path.push(*node);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.children(node) {
+                if *next != from && !prev.contains_key(next) {
+                    prev.insert(*next, node);
+                    todo.push_back(*next);
+                }
+            }
+        }
+        None
+    }
+
+    /// all nodes ordered such that each comes after everything it's derived from
+    pub fn topological(&self) -> Vec<Node> {
+        let mut indegree = self
+            .nodes()
+            .into_iter()
+            .map(|node| (node, self.parents(node).len()))
+            .collect::<HashMap<_, _>>();
+        let mut ready = indegree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(node, _)| *node)
+            .collect::<BTreeSet<_>>();
+        let mut order = Vec::new();
+        while let Some(node) = ready.pop_first() {
+            order.push(node);
+            for next in self.children(node) {
+                if let Some(degree) = indegree.get_mut(next) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.insert(*next);
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    /// the graph in the stable json schema
+    pub fn graph(&self) -> LineageGraph {
+        let nodes = self
+            .nodes()
+            .into_iter()
+            .filter_map(|node| match node {
+                Node::Specter(id) => {
+                    if let Some(entity) = self.ring.entities.get(&id) {
+                        Some(LineageNode::Entity {
+                            id,
+                            ext: entity.ext,
+                        })
+                    } else {
+                        let specter = self.ring.specters.get(&id)?;
+                        Some(LineageNode::Specter {
+                            id,
+                            ext: specter.ext,
+                            failed: specter.body.failure.is_some(),
+                        })
+                    }
+                }
+                Node::Operation(id) => {
+                    let operation = self.ring.operations.get(&id)?;
+                    Some(LineageNode::Operation {
+                        id,
+                        kind: operation.kind,
+                        attr: operation.attr.clone(),
+                    })
+                }
+            })
+            .collect();
+        let mut edges = Vec::new();
+        for from in self.nodes() {
+            for to in self.children(from) {
+                let role = match (from, to) {
+                    (Node::Operation(op), Node::Specter(id)) => {
+                        self.ring.operations.get(&op).and_then(|operation| {
+                            operation
+                                .outputs
+                                .iter()
+                                .find(|output| output.specter == *id)
+                                .map(|output| output.role)
+                        })
+                    }
+                    _ => None,
+                };
+                edges.push(LineageEdge {
+                    from: from.stem(),
+                    to: to.stem(),
+                    role,
+                });
+            }
+        }
+        LineageGraph {
+            version: LineageGraph::VERSION,
+            nodes,
+            edges,
+        }
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(&self.graph())?)
+    }
+
+    /// the graph in graphviz dot
+    pub fn to_dot(&self) -> String {
+        let graph = self.graph();
+        let mut dot = String::from("digraph lineage {\n    rankdir=LR;\n");
+        for node in graph.nodes {
+            let (id, attrs) = match node {
+                LineageNode::Entity { id, ext } => (
+                    id.stem(),
+                    format!(
+                        "shape=box, label=\"{}\"",
+                        escape(&format!("{}\n{}", ext, id.stem()))
+                    ),
+                ),
+                LineageNode::Specter { id, ext, failed } => (
+                    id.stem(),
+                    format!(
+                        "shape=box, style=dashed, color={}, label=\"{}\"",
+                        if failed { "red" } else { "black" },
+                        escape(&format!("{}\n{}", ext, id.stem()))
+                    ),
+                ),
+                LineageNode::Operation { id, kind, .. } => (
+                    id.stem(),
+                    format!(
+                        "shape=ellipse, label=\"{}\"",
+                        escape(&format!("{:?}", kind))
+                    ),
+                ),
+            };
+            dot += &format!("    \"{}\" [{}];\n", id, attrs);
+        }
+        for edge in graph.edges {
+            match edge.role {
+                Some(role) => {
+                    dot += &format!(
+                        "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                        edge.from,
+                        edge.to,
+                        escape(&format!("{:?}", role))
+                    )
+                }
+                None => dot += &format!("    \"{}\" -> \"{}\";\n", edge.from, edge.to),
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+/// escape the text for a quoted dot string, with line breaks kept as such
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// the lineage graph in a stable schema for inspection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageGraph {
+    /// the version of the schema
+    pub version: u32,
+    pub nodes: Vec<LineageNode>,
+    pub edges: Vec<LineageEdge>,
+}
+
+impl LineageGraph {
+    pub const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LineageNode {
+    Entity {
+        id: MioId,
+        ext: EntityExt,
+    },
+    Specter {
+        id: MioId,
+        ext: EntityExt,
+        /// whether the last attempt to actualize it failed
+        failed: bool,
+    },
+    Operation {
+        id: OpId,
+        kind: OperationKind,
+        attr: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageEdge {
+    pub from: String,
+    pub to: String,
+    /// the role of the resulting specter, for edges from an operation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// an entity `a` with two operations upon it whose results `b` and `c` are joined into `d`,
+    /// and an unrelated entity `e`
+    fn diamond() -> (MioRing, [Node; 8]) {
+        let mut ring = MioRing::new();
+        let mut ord = 0;
+        let mut next = || {
+            ord += 1;
+            RingId::now(ord)
+        };
+        let [a, e] = [next(), next()].map(MioId::from);
+        for id in [a, e] {
+            ring.entities.insert(
+                id,
+                Specter {
+                    id,
+                    ext: EntityExt::Png,
+                    nonce: Vec::new(),
+                    deps: Vec::new(),
+                    body: Concrete {
+                        pool: AllocPool::default(),
+                        providence: Providence::Registered,
+                        metadata: Metadata::default(),
+                    },
+                },
+            );
+        }
+        let mut operate = |kind, base: Vec<MioId>| {
+            let (op, id) = (OpId::from(next()), MioId::from(next()));
+            ring.specters.insert(
+                id,
+                Specter {
+                    id,
+                    ext: EntityExt::Png,
+                    nonce: Vec::new(),
+                    deps: Vec::new(),
+                    body: Lazy {
+                        operation: op,
+                        failure: None,
+                    },
+                },
+            );
+            ring.operations.insert(
+                op,
+                Operation {
+                    id: op,
+                    kind,
+                    attr: serde_json::Value::Null,
+                    base,
+                    outputs: vec![Output {
+                        role: Role::Primary,
+                        specter: id,
+                    }],
+                    dropped: Vec::new(),
+                },
+            );
+            (Node::Operation(op), id)
+        };
+        let (to_b, b) = operate(OperationKind::Crop, vec![a]);
+        let (to_c, c) = operate(OperationKind::Resize, vec![a]);
+        let (to_d, d) = operate(OperationKind::Custom(1), vec![b, c]);
+        let [a, b, c, d, e] = [a, b, c, d, e].map(Node::Specter);
+        (ring, [a, to_b, b, to_c, c, to_d, d, e])
+    }
+
+    #[test]
+    fn ancestors_and_descendants() {
+        let (ring, [a, to_b, b, to_c, c, to_d, d, e]) = diamond();
+        let lineage = ring.lineage();
+        assert_eq!(
+            lineage.ancestors(d),
+            BTreeSet::from([a, to_b, b, to_c, c, to_d])
+        );
+        assert_eq!(lineage.ancestors(b), BTreeSet::from([a, to_b]));
+        assert_eq!(
+            lineage.descendants(a),
+            BTreeSet::from([to_b, b, to_c, c, to_d, d])
+        );
+        assert!(lineage.descendants(d).is_empty());
+        assert!(lineage.ancestors(e).is_empty() && lineage.descendants(e).is_empty());
+        assert_eq!(lineage.roots(), vec![a, e]);
+        assert_eq!(lineage.parents(to_d), [b, c]);
+    }
+
+    #[test]
+    fn paths() {
+        let (ring, [a, to_b, b, to_c, c, to_d, d, e]) = diamond();
+        let lineage = ring.lineage();
+        let path = lineage.path(a, d).unwrap();
+        assert!(path == [a, to_b, b, to_d, d] || path == [a, to_c, c, to_d, d]);
+        assert_eq!(lineage.path(c, d).unwrap(), [c, to_d, d]);
+        assert_eq!(lineage.path(a, a).unwrap(), [a]);
+        // derivation only goes one way
+        assert_eq!(lineage.path(d, a), None);
+        assert_eq!(lineage.path(b, c), None);
+        assert_eq!(lineage.path(a, e), None);
+    }
+
+    #[test]
+    fn topological_order() {
+        let (ring, nodes) = diamond();
+        let lineage = ring.lineage();
+        let order = lineage.topological();
+        assert_eq!(order.len(), nodes.len());
+        let at = |node| order.iter().position(|n| *n == node).unwrap();
+        for node in nodes {
+            for parent in lineage.parents(node) {
+                assert!(at(*parent) < at(node), "{:?} before {:?}", parent, node);
+            }
+        }
+    }
+
+    #[test]
+    fn dot_labels_are_escaped() {
+        assert_eq!(escape(r#"say "mio"\n"#), r#"say \"mio\"\\n"#);
+        assert_eq!(escape("png\nstem"), r"png\nstem");
+
+        let (ring, [a, to_b, b, ..]) = diamond();
+        let dot = ring.lineage().to_dot();
+        assert!(dot.starts_with("digraph lineage {") && dot.ends_with("}\n"));
+        assert!(dot.contains(&format!(
+            "\"{}\" [shape=box, label=\"png\\n{}\"];",
+            a.stem(),
+            a.stem()
+        )));
+        assert!(dot.contains(&format!(
+            "\"{}\" [shape=ellipse, label=\"Crop\"];",
+            to_b.stem()
+        )));
+        assert!(dot.contains(&format!(
+            "\"{}\" -> \"{}\" [label=\"Primary\"];",
+            to_b.stem(),
+            b.stem()
+        )));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\";", a.stem(), to_b.stem())));
+        // every quote is either escaped or delimits a string
+        for line in dot.lines() {
+            let unescaped = line.replace("\\\\", "").replace("\\\"", "");
+            assert_eq!(unescaped.matches('"').count() % 2, 0, "{}", line);
+        }
+    }
+}
@@ -1,103 +1,233 @@
 use super::*;
+use sha2::{Digest, Sha256};
+use std::sync::PoisonError;
 
-impl EntityKind {
-    pub fn synthesize(&self) -> Vec<OperationKind> {
-        let mut v = Vec::new();
+/// the reasons why an operation can't be done upon its base specters
+#[derive(Debug)]
+pub enum OperationError {
+    /// no base specter is given
+    NoBase,
+    /// the base specter is not in the ring
+    Missing(MioId),
+    /// the operation resulting in a lazy specter is not in the ring
+    Unresolved(OpId),
+    /// no registered operation of the kind accepts base specters of the kinds
+    Unsupported {
+        kind: OperationKind,
+        src: Vec<EntityKind>,
+    },
+    /// the attributes don't fit the operable
+    Attribute(serde_json::Error),
+    /// the attributes are well-formed but make no sense
+    Invalid(String),
+    /// the region reaches outside of the base image
+    OutOfBounds {
+        region: (u32, u32, u32, u32),
+        bounds: (u32, u32),
+    },
+    /// the language is not installed for ocr
+    Language(String),
+    /// the format of the base specter is not supported
+    Format(EntityExt),
+    /// the base specter can't be read
+    Unreadable(anyhow::Error),
+}
+
+impl Display for OperationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            EntityKind::Text => {}
-            EntityKind::Image => {
-                #[cfg(feature = "image")]
-                {
-                    v.push(OperationKind::Annotation);
-                    v.push(OperationKind::Crop);
-                }
-                #[cfg(feature = "ocr")]
-                {
-                    v.push(OperationKind::As(EntityKind::Text));
-                }
+            OperationError::NoBase => write!(f, "operation has no base"),
+            OperationError::Missing(id) => write!(f, "base {} is not in the ring", id.stem()),
+            OperationError::Unresolved(op) => {
+                write!(f, "operation {} is not in the ring", op.stem())
             }
-            EntityKind::Audio => {}
-            EntityKind::Video => {}
+            OperationError::Unsupported { kind, src } => {
+                write!(f, "operation {:?} is not supported for {:?}", kind, src)
+            }
+            OperationError::Attribute(e) => write!(f, "invalid attributes: {}", e),
+            OperationError::Invalid(reason) => write!(f, "invalid attributes: {}", reason),
+            OperationError::OutOfBounds {
+                region: (x, y, width, height),
+                bounds: (w, h),
+            } => write!(
+                f,
+                "region {}x{}+{}+{} is out of the {}x{} bounds",
+                width, height, x, y, w, h
+            ),
+            OperationError::Language(lang) => write!(f, "language {} is not installed", lang),
+            OperationError::Format(ext) => write!(f, "format {} is not supported", ext),
+            OperationError::Unreadable(e) => write!(f, "base can't be read: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for OperationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OperationError::Attribute(e) => Some(e),
+            OperationError::Unreadable(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for OperationError {
+    fn from(e: serde_json::Error) -> Self {
+        OperationError::Attribute(e)
+    }
+}
+
+/// the image formats that can be both decoded and encoded
+pub const IMAGE_FORMATS: &[EntityExt] = &[EntityExt::Png, EntityExt::Jpg, EntityExt::Webp];
+
+/// ensure that the resulting text is of a text format
+#[cfg(feature = "ocr")]
+fn expect_text(ext: EntityExt) -> Result<(), OperationError> {
+    match ext.kind() {
+        EntityKind::Text => Ok(()),
+        _ => Err(OperationError::Format(ext)),
+    }
+}
+
+/// ensure that all base specters are of the formats
+fn expect_formats(base: &[MioId], mio: &Mio, formats: &[EntityExt]) -> Result<(), OperationError> {
+    for id in base {
+        let ext = mio
+            .specterish(id)
+            .map_err(|_| OperationError::Missing(*id))?
+            .extension();
+        if !formats.contains(&ext) {
+            return Err(OperationError::Format(ext));
         }
-        v
+    }
+    Ok(())
+}
+
+impl EntityKind {
+    /// all operations that can be done upon a single specter of the kind
+    pub fn synthesize(&self, registry: &OperationRegistry) -> Vec<OperationKind> {
+        registry
+            .providers()
+            .filter(|provider| provider.accepts(&[*self]))
+            .map(|provider| provider.kind())
+            .unique()
+            .collect()
     }
 }
 
 impl OperationKind {
-    pub fn analyze(&self, src: impl IntoIterator<Item = EntityKind>) -> anyhow::Result<EntityKind> {
+    /// the kind of the primary resulting specter upon base specters of the kinds,
+    /// as told by the provider
+    pub fn analyze(
+        &self,
+        registry: &OperationRegistry,
+        src: impl IntoIterator<Item = EntityKind>,
+    ) -> anyhow::Result<EntityKind> {
         let src = src.into_iter().collect::<Vec<_>>();
-        match (src.as_slice(), self) {
-            #[cfg(feature = "image")]
-            (&[EntityKind::Image], OperationKind::Crop) => Ok(EntityKind::Image),
-            #[cfg(feature = "image")]
-            (&[EntityKind::Image], OperationKind::Annotation) => Ok(EntityKind::Image),
-            #[cfg(feature = "ocr")]
-            (&[EntityKind::Image], OperationKind::As(EntityKind::Text)) => Ok(EntityKind::Text),
-            _ => Err(anyhow::anyhow!(
-                "operation {:?} is not supported for {:?}",
-                self,
-                src
-            )),
+        Ok(registry.find(self, &src)?.ext().kind())
+    }
+}
+
+impl Operation {
+    /// the identifier of the resulting specter that plays the role
+    pub fn output(&self, role: Role) -> Option<MioId> {
+        self.outputs
+            .iter()
+            .find(|output| output.role == role)
+            .map(|output| output.specter)
+    }
+    /// the identifiers of all resulting specters
+    pub fn specters(&self) -> impl Iterator<Item = MioId> + '_ {
+        self.outputs.iter().map(|output| output.specter)
+    }
+    /// write the executed results to the resulting specters by their roles,
+    /// all of which must have been laid out when the operation was initiated;
+    /// the results of dropped roles are let go
+    pub fn write(&self, mio: &Mio, mut res: Vec<(Role, Vec<u8>)>) -> anyhow::Result<()> {
+        res.retain(|(role, _)| !self.dropped.contains(role));
+        if res.is_empty() {
+            anyhow::bail!("operation yields none of its outputs")
         }
+        let outputs = res
+            .iter()
+            .map(|(role, _)| {
+                self.output(*role).ok_or_else(|| {
+                    anyhow::anyhow!("operation yields {:?}, which is not laid out", role)
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let Fingerprint(fingerprint) = self.fingerprint(mio)?;
+        for (id, (_, res)) in outputs.into_iter().zip(res) {
+            mio.specterish(&id)?.write(&mio.dirs, &res)?;
+            if let Some(specter) = mio.ring.specters.get(&id) {
+                fs::write(specter.fingerprint_path(&mio.dirs), &fingerprint)?;
+            }
+        }
+        Ok(())
+    }
+    /// the registered provider of the operation
+    pub fn provider<'a>(&self, mio: &'a Mio) -> anyhow::Result<&'a dyn Provider> {
+        let src = self
+            .base
+            .iter()
+            .map(|base| Ok(mio.specterish(base)?.kind()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(mio.registry.find(&self.kind, &src)?)
+    }
+    /// hash the version of the provider, the attributes and the contents of the bases as they are
+    pub fn fingerprint(&self, mio: &Mio) -> anyhow::Result<Fingerprint> {
+        let mut hasher = Sha256::new();
+        hasher.update(self.provider(mio)?.version().as_bytes());
+        hasher.update(serde_json::to_vec(&self.attr)?);
+        for base in self.base.iter() {
+            let base = mio.specterish(base)?;
+            // the ciphertext is determined by the content under the fixed nonce of the specter,
+            // so hashing it spares the decryption
+            hasher.update(mio.digests.digest(&base.locate(&mio.dirs))?);
+        }
+        Ok(Fingerprint(format!("{:x}", hasher.finalize())))
     }
 }
 
-impl Actualizable for Operation {
-    /// an enum dispatch for all
-    fn run(&self, mio: &Mio) -> anyhow::Result<()> {
-        let base = self.base.clone();
-        let _res_kind = self
-            .kind
-            .analyze(base.iter().map(|base| mio.specterish(&base).kind()))?;
-        match self.kind {
-            OperationKind::Annotation => {
-                let _base = base.into_iter().exactly_one()?;
-                todo!()
-            }
-            OperationKind::Trim => todo!(),
-            OperationKind::Crop => {
-                let base = base.into_iter().exactly_one()?;
-                #[cfg(feature = "image")]
-                {
-                    // ensure that the base is actualized
-                    mio.specterish(&base).run(mio)?;
-                    let res = image_impl::CropImage::prepare(self)?
-                        .execute(mio.specterish(&base).read_as_temp(&mio.dirs)?)?;
-                    mio.specterish(&self.specter)
-                        .write(&mio.dirs, res.as_bytes())
-                }
-                #[cfg(not(feature = "image"))]
-                {
-                    anyhow::bail!("feature `image` is not enabled")
-                }
-            }
-            OperationKind::Resize => todo!(),
-            OperationKind::As(EntityKind::Text) => {
-                let base = base.into_iter().exactly_one()?;
-                match mio.specterish(&base).kind() {
-                    EntityKind::Text => Ok(()),
-                    EntityKind::Image => {
-                        #[cfg(feature = "ocr")]
-                        {
-                            // ensure that the base is actualized
-                            mio.specterish(&base).run(mio)?;
-                            let res = ocr_impl::OcrText::prepare(self)?
-                                .execute(mio.specterish(&base).read_as_temp(&mio.dirs)?)?;
-                            mio.specterish(&self.specter)
-                                .write(&mio.dirs, res.as_bytes())
-                        }
-                        #[cfg(not(feature = "ocr"))]
-                        {
-                            anyhow::bail!("feature `ocr` is not enabled")
-                        }
-                    }
-                    EntityKind::Audio => anyhow::bail!("audio to text is not supported"),
-                    EntityKind::Video => anyhow::bail!("video to text is not supported"),
-                }
+/// the content hashes of files, each kept as long as the modification time and size of the file
+/// hold, so that checking freshness doesn't read every base over again
+#[derive(Debug, Default)]
+pub struct Digests {
+    inner: Mutex<HashMap<PathBuf, Digested>>,
+}
+
+#[derive(Debug)]
+struct Digested {
+    modified: SystemTime,
+    len: u64,
+    digest: Vec<u8>,
+}
+
+impl Digests {
+    pub fn digest(&self, path: &Path) -> anyhow::Result<Vec<u8>> {
+        let metadata = fs::metadata(path)?;
+        let (modified, len) = (metadata.modified()?, metadata.len());
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(digested) = inner.get(path) {
+            if (digested.modified, digested.len) == (modified, len) {
+                return Ok(digested.digest.clone());
             }
-            OperationKind::As(_) => todo!(),
-            OperationKind::Summarize => todo!(),
         }
+        let digest = Sha256::digest(fs::read(path)?).to_vec();
+        let digested = Digested {
+            modified,
+            len,
+            digest: digest.clone(),
+        };
+        inner.insert(path.to_path_buf(), digested);
+        Ok(digest)
+    }
+}
+
+impl Actualizable for Operation {
+    /// dispatch to the registered provider of the operation
+    fn run(&self, mio: &Mio) -> anyhow::Result<()> {
+        self.provider(mio)?.run(self, mio)
     }
 }
 
@@ -105,6 +235,41 @@ impl Actualizable for Operation {
 mod image_impl {
     use super::*;
 
+    use image::{DynamicImage, ImageOutputFormat};
+    use std::io::Cursor;
+
+    /// encode the image in the format of the extension; `quality` only applies to jpeg
+    fn encode(img: &DynamicImage, ext: EntityExt, quality: Option<u8>) -> anyhow::Result<Vec<u8>> {
+        let mut buf = Cursor::new(Vec::new());
+        match ext {
+            EntityExt::Png => img.write_to(&mut buf, ImageOutputFormat::Png)?,
+            // jpeg has no alpha channel
+            EntityExt::Jpg => DynamicImage::ImageRgb8(img.to_rgb8())
+                .write_to(&mut buf, ImageOutputFormat::Jpeg(quality.unwrap_or(90)))?,
+            // only the lossless webp encoder is available
+            EntityExt::Webp => img.write_to(&mut buf, ImageOutputFormat::WebP)?,
+            _ => anyhow::bail!("can't encode image as {}", ext),
+        }
+        Ok(buf.into_inner())
+    }
+
+    /// ensure that the image can be encoded as requested
+    fn expect_encodable(ext: EntityExt, quality: Option<u8>) -> Result<(), OperationError> {
+        if !IMAGE_FORMATS.contains(&ext) {
+            return Err(OperationError::Format(ext));
+        }
+        match quality {
+            Some(quality) if !(1..=100).contains(&quality) => Err(OperationError::Invalid(
+                format!("quality {} is not within 1..=100", quality),
+            )),
+            // only the lossless webp encoder is available
+            Some(_) if ext == EntityExt::Webp => Err(OperationError::Invalid(
+                "webp is only encoded losslessly, without a quality".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
     #[derive(Serialize, Deserialize)]
     pub struct CropImage {
         pub ext: EntityExt,
@@ -114,43 +279,923 @@ mod image_impl {
         pub height: u32,
     }
 
+    impl Registrable for CropImage {
+        const NAME: &'static str = "Crop";
+        const ACCEPTS: &'static [EntityKind] = &[EntityKind::Image];
+        const EXT: EntityExt = EntityExt::Png;
+        fn operation() -> OperationKind {
+            OperationKind::Crop
+        }
+    }
+
+    impl CropImage {
+        /// ensure that the region is within the bounds of the image
+        fn expect_within(&self, bounds: (u32, u32)) -> Result<(), OperationError> {
+            let fits = |at: u32, len: u32, bound: u32| {
+                len > 0 && at.checked_add(len).is_some_and(|end| end <= bound)
+            };
+            if !fits(self.x, self.width, bounds.0) || !fits(self.y, self.height, bounds.1) {
+                return Err(OperationError::OutOfBounds {
+                    region: (self.x, self.y, self.width, self.height),
+                    bounds,
+                });
+            }
+            Ok(())
+        }
+    }
+
     impl Operable for CropImage {
         fn kind(&self) -> OperationKind {
             OperationKind::Crop
         }
-        fn execute<'a>(self, src: NamedTempFile) -> anyhow::Result<Vec<u8>> {
+        fn validate(&self, base: &[MioId], mio: &Mio) -> Result<(), OperationError> {
+            expect_formats(base, mio, IMAGE_FORMATS)?;
+            expect_encodable(self.ext, None)?;
+            for id in base {
+                let specter = mio
+                    .specterish(id)
+                    .map_err(|_| OperationError::Missing(*id))?;
+                // lazy bases can only be measured once actualized
+                if !specter.exists(&mio.dirs) {
+                    continue;
+                }
+                let src = specter
+                    .read_as_temp(&mio.dirs)
+                    .map_err(OperationError::Unreadable)?;
+                let bounds = image::image_dimensions(src.path())
+                    .map_err(|e| OperationError::Unreadable(e.into()))?;
+                self.expect_within(bounds)?;
+            }
+            Ok(())
+        }
+        fn outputs(&self) -> Vec<(Role, EntityExt)> {
+            vec![(Role::Primary, self.ext)]
+        }
+        fn execute(self, src: NamedTempFile) -> anyhow::Result<Vec<(Role, Vec<u8>)>> {
             let img = image::open(src.path())?;
+            // lazy bases are only measured now
+            self.expect_within((img.width(), img.height()))?;
             let img = img.crop_imm(self.x, self.y, self.width, self.height);
-            img.save(src.path())?;
-            Ok(std::fs::read(src.path())?)
+            Ok(vec![(Role::Primary, encode(&img, self.ext, None)?)])
+        }
+    }
+
+    /// convert the image to another format
+    #[derive(Serialize, Deserialize)]
+    pub struct ConvertImage {
+        pub ext: EntityExt,
+        /// the quality of lossy formats, from 1 to 100
+        pub quality: Option<u8>,
+    }
+
+    impl Registrable for ConvertImage {
+        const NAME: &'static str = "Convert";
+        const ACCEPTS: &'static [EntityKind] = &[EntityKind::Image];
+        const EXT: EntityExt = EntityExt::Png;
+        fn operation() -> OperationKind {
+            OperationKind::As(EntityKind::Image)
+        }
+    }
+
+    impl Operable for ConvertImage {
+        fn kind(&self) -> OperationKind {
+            OperationKind::As(EntityKind::Image)
+        }
+        fn validate(&self, base: &[MioId], mio: &Mio) -> Result<(), OperationError> {
+            expect_formats(base, mio, IMAGE_FORMATS)?;
+            expect_encodable(self.ext, self.quality)
+        }
+        fn outputs(&self) -> Vec<(Role, EntityExt)> {
+            vec![(Role::Primary, self.ext)]
+        }
+        fn execute(self, src: NamedTempFile) -> anyhow::Result<Vec<(Role, Vec<u8>)>> {
+            let img = image::open(src.path())?;
+            Ok(vec![(Role::Primary, encode(&img, self.ext, self.quality)?)])
         }
     }
 }
-use image::EncodableLayout;
 #[cfg(feature = "image")]
 pub use image_impl::*;
 
 #[cfg(feature = "ocr")]
 mod ocr_impl {
     use super::*;
+    use image::{imageops::FilterType, DynamicImage, GrayImage};
+    use std::process::Command;
 
     #[derive(Serialize, Deserialize)]
     pub struct OcrText {
         pub ext: EntityExt,
+        /// the languages of tesseract joined by `+`, e.g. `eng+jpn+chi_sim`
         pub lang: String,
+        /// detect the script of the image and put the languages of `lang` written in it first;
+        /// relies on the `tesseract` executable and its `osd` data being installed
+        #[serde(default)]
+        pub detect: bool,
+        /// the page segmentation mode of tesseract, from 0 to 13
+        #[serde(default)]
+        pub psm: Option<u8>,
+        /// the steps applied to the image before recognition, in order
+        #[serde(default)]
+        pub preprocess: Vec<Preprocess>,
+    }
+
+    impl Default for OcrText {
+        fn default() -> Self {
+            Self {
+                ext: EntityExt::Txt,
+                lang: "eng".to_string(),
+                detect: false,
+                psm: None,
+                preprocess: Vec::new(),
+            }
+        }
+    }
+
+    impl Registrable for OcrText {
+        const NAME: &'static str = "OCR";
+        const ACCEPTS: &'static [EntityKind] = &[EntityKind::Image];
+        const EXT: EntityExt = EntityExt::Txt;
+        fn operation() -> OperationKind {
+            OperationKind::As(EntityKind::Text)
+        }
+    }
+
+    impl OcrText {
+        /// ensure that tesseract can be initialized with the language
+        pub(super) fn expect_lang(lang: &str) -> Result<(), OperationError> {
+            leptess::LepTess::new(None, lang)
+                .map(|_| ())
+                .map_err(|_| OperationError::Language(lang.to_string()))
+        }
+        /// the script of the image, as named by the orientation and script detection of tesseract
+        fn script(src: &Path) -> anyhow::Result<String> {
+            let output = Command::new("tesseract")
+                .arg(src)
+                .args(["stdout", "--psm", "0"])
+                .output()?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "tesseract failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )
+            }
+            String::from_utf8(output.stdout)?
+                .lines()
+                .find_map(|line| line.strip_prefix("Script:"))
+                .map(|script| script.trim().to_string())
+                .ok_or_else(|| anyhow::anyhow!("no script is detected"))
+        }
+        /// the script the language is written in, as named by tesseract
+        fn script_of(lang: &str) -> &'static str {
+            match lang.trim_end_matches("_vert") {
+                "jpn" => "Japanese",
+                "chi_sim" | "chi_tra" => "Han",
+                "kor" => "Korean",
+                "rus" | "ukr" | "bel" | "bul" | "srp" | "mkd" | "kaz" => "Cyrillic",
+                "ara" | "fas" | "urd" => "Arabic",
+                "ell" | "grc" => "Greek",
+                "heb" | "yid" => "Hebrew",
+                "hin" | "mar" | "nep" | "san" => "Devanagari",
+                "tha" => "Thai",
+                _ => "Latin",
+            }
+        }
+        /// the languages to recognize the image with in a single pass;
+        /// all of them are kept so that text mixing languages is still recognized
+        fn langs(&self, src: &Path) -> String {
+            if !self.detect {
+                return self.lang.clone();
+            }
+            match Self::script(src) {
+                Ok(script) => {
+                    let (detected, rest): (Vec<_>, Vec<_>) = self
+                        .lang
+                        .split('+')
+                        .partition(|lang| Self::script_of(lang) == script);
+                    detected.into_iter().chain(rest).join("+")
+                }
+                Err(e) => {
+                    log::warn!("can't detect the script, keeping the languages: {:#}", e);
+                    self.lang.clone()
+                }
+            }
+        }
+        /// recognize the image with the language, yielding the text and its layout
+        fn recognize(&self, lang: &str, src: &Path) -> anyhow::Result<(String, OcrLayout)> {
+            let mut lt = leptess::LepTess::new(None, lang)?;
+            if let Some(psm) = self.psm {
+                lt.set_variable(leptess::Variable::TesseditPagesegMode, &psm.to_string())?;
+            }
+            lt.set_image(src)?;
+            let text = lt.get_utf8_text()?;
+            let mut layout = OcrLayout::from_tsv(&lt.get_tsv_text(0)?, lt.mean_text_conf() as f32);
+            layout.lang = lang.to_string();
+            Ok((text, layout))
+        }
     }
 
     impl Operable for OcrText {
         fn kind(&self) -> OperationKind {
             OperationKind::As(EntityKind::Text)
         }
-        fn execute<'a>(self, src: NamedTempFile) -> anyhow::Result<Vec<u8>> {
-            let mut lt = leptess::LepTess::new(None, self.lang.as_str()).unwrap();
-            lt.set_image(src.path()).unwrap();
-            let text = lt.get_utf8_text().unwrap();
-            Ok(Vec::from(text.as_bytes()))
+        fn validate(&self, base: &[MioId], mio: &Mio) -> Result<(), OperationError> {
+            expect_formats(base, mio, IMAGE_FORMATS)?;
+            expect_text(self.ext)?;
+            if self.psm.is_some_and(|psm| psm > 13) {
+                return Err(OperationError::Invalid(
+                    "page segmentation mode must be within 0..=13".to_string(),
+                ));
+            }
+            for step in self.preprocess.iter() {
+                step.validate()?;
+            }
+            Self::expect_lang(&self.lang)
+        }
+        /// the plain text as primary, and the layout of the text as a json sidecar
+        fn outputs(&self) -> Vec<(Role, EntityExt)> {
+            vec![(Role::Primary, self.ext), (Role::Sidecar, EntityExt::Json)]
+        }
+        fn execute(self, src: NamedTempFile) -> anyhow::Result<Vec<(Role, Vec<u8>)>> {
+            let (src, scale) = if self.preprocess.is_empty() {
+                (src, 1.0)
+            } else {
+                let mut img = image::open(src.path())?;
+                let mut scale = 1.0;
+                for step in self.preprocess.iter() {
+                    (img, scale) = (step.apply(img), scale * step.scale());
+                }
+                let mut temp = tempfile::Builder::new().suffix(".png").tempfile()?;
+                img.write_to(&mut temp, image::ImageOutputFormat::Png)?;
+                (temp, scale)
+            };
+            let (text, mut layout) = self.recognize(&self.langs(src.path()), src.path())?;
+            // bounding boxes are reported in the coordinates of the original image
+            layout.rescale(1.0 / scale);
+            Ok(vec![
+                (Role::Primary, Vec::from(text.as_bytes())),
+                (Role::Sidecar, serde_json::to_vec(&layout)?),
+            ])
         }
     }
+
+    /// a rectangle in the pixel coordinates of the image
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct OcrBox {
+        pub left: u32,
+        pub top: u32,
+        pub width: u32,
+        pub height: u32,
+    }
+
+    impl OcrBox {
+        /// whether the center of the box falls within the other box
+        pub fn within(&self, other: &OcrBox) -> bool {
+            let (x, y) = (
+                self.left.saturating_add(self.width / 2),
+                self.top.saturating_add(self.height / 2),
+            );
+            (other.left..other.left.saturating_add(other.width)).contains(&x)
+                && (other.top..other.top.saturating_add(other.height)).contains(&y)
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct OcrWord {
+        pub text: String,
+        pub bbox: OcrBox,
+        /// the confidence of the recognition, from 0 to 100
+        pub confidence: f32,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct OcrLine {
+        pub text: String,
+        pub bbox: OcrBox,
+        /// the mean confidence of the words
+        pub confidence: f32,
+        pub words: Vec<OcrWord>,
+    }
+
+    impl OcrBox {
+        fn rescale(&mut self, factor: f32) {
+            let scale = |v: u32| (v as f32 * factor).round() as u32;
+            *self = OcrBox {
+                left: scale(self.left),
+                top: scale(self.top),
+                width: scale(self.width),
+                height: scale(self.height),
+            };
+        }
+    }
+
+    /// the recognized text laid out as lines of words, stored as the sidecar of `OcrText`
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct OcrLayout {
+        /// the language that the text is recognized with
+        #[serde(default)]
+        pub lang: String,
+        /// the mean confidence of the whole image
+        pub confidence: f32,
+        pub lines: Vec<OcrLine>,
+    }
+
+    impl OcrLayout {
+        /// collect the lines and words out of the tsv output of tesseract
+        pub fn from_tsv(tsv: &str, confidence: f32) -> Self {
+            // level, page, block, paragraph, line, word, left, top, width, height, conf, text
+            let mut lines: Vec<([&str; 4], OcrLine)> = Vec::new();
+            for row in tsv.lines().skip(1) {
+                let cols = row.splitn(12, '\t').collect::<Vec<_>>();
+                let [level, page, block, par, line, _, left, top, width, height, conf, text] =
+                    cols[..]
+                else {
+                    continue;
+                };
+                let key = [page, block, par, line];
+                let bbox = OcrBox {
+                    left: left.parse().unwrap_or_default(),
+                    top: top.parse().unwrap_or_default(),
+                    width: width.parse().unwrap_or_default(),
+                    height: height.parse().unwrap_or_default(),
+                };
+                match level {
+                    "4" => lines.push((
+                        key,
+                        OcrLine {
+                            text: String::new(),
+                            bbox,
+                            confidence: 0.0,
+                            words: Vec::new(),
+                        },
+                    )),
+                    "5" if !text.trim().is_empty() => {
+                        if let Some((_, line)) = lines.iter_mut().rev().find(|(k, _)| *k == key) {
+                            line.words.push(OcrWord {
+                                text: text.trim().to_string(),
+                                bbox,
+                                confidence: conf.parse().unwrap_or_default(),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let lines = lines
+                .into_iter()
+                .map(|(_, line)| line)
+                .filter(|line| !line.words.is_empty())
+                .map(|mut line| {
+                    line.text = line.words.iter().map(|word| word.text.as_str()).join(" ");
+                    line.confidence = line.words.iter().map(|word| word.confidence).sum::<f32>()
+                        / line.words.len() as f32;
+                    line
+                })
+                .collect();
+            Self {
+                lang: String::new(),
+                confidence,
+                lines,
+            }
+        }
+
+        /// scale all bounding boxes by the factor
+        pub fn rescale(&mut self, factor: f32) {
+            for line in self.lines.iter_mut() {
+                line.bbox.rescale(factor);
+                for word in line.words.iter_mut() {
+                    word.bbox.rescale(factor);
+                }
+            }
+        }
+
+        pub fn words(&self) -> impl Iterator<Item = &OcrWord> {
+            self.lines.iter().flat_map(|line| line.words.iter())
+        }
+
+        /// the words containing the query, case-insensitively
+        pub fn search<'a>(&'a self, query: &str) -> Vec<&'a OcrWord> {
+            let query = query.to_lowercase();
+            self.words()
+                .filter(|word| word.text.to_lowercase().contains(&query))
+                .collect()
+        }
+
+        /// the text of the words within the region, line by line
+        pub fn text_within(&self, region: &OcrBox) -> String {
+            self.lines
+                .iter()
+                .map(|line| {
+                    line.words
+                        .iter()
+                        .filter(|word| word.bbox.within(region))
+                        .map(|word| word.text.as_str())
+                        .join(" ")
+                })
+                .filter(|line| !line.is_empty())
+                .join("\n")
+        }
+    }
+
+    /// a step to make the image friendlier to tesseract
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub enum Preprocess {
+        Grayscale,
+        /// invert the colors; with `auto`, only if the image is mostly dark, e.g. a dark mode ui
+        Invert {
+            auto: bool,
+        },
+        /// enlarge the image by the factor, which helps with small text
+        Upscale {
+            factor: f32,
+        },
+        /// turn the image into black and white at the threshold, or by otsu's method if `None`
+        Binarize {
+            threshold: Option<u8>,
+        },
+        /// rotate the image so that the text lines are level
+        Deskew,
+    }
+
+    impl Preprocess {
+        fn validate(&self) -> Result<(), OperationError> {
+            match self {
+                Preprocess::Upscale { factor } if !(1.0..=8.0).contains(factor) => {
+                    Err(OperationError::Invalid(format!(
+                        "upscale factor {} is not within 1..=8",
+                        factor
+                    )))
+                }
+                _ => Ok(()),
+            }
+        }
+
+        /// how much the step enlarges the image
+        fn scale(&self) -> f32 {
+            match self {
+                Preprocess::Upscale { factor } => *factor,
+                _ => 1.0,
+            }
+        }
+
+        pub fn apply(&self, img: DynamicImage) -> DynamicImage {
+            match *self {
+                Preprocess::Grayscale => img.grayscale(),
+                Preprocess::Invert { auto } => {
+                    let mut img = img;
+                    if !auto || mean_luma(&img.to_luma8()) < 128.0 {
+                        img.invert();
+                    }
+                    img
+                }
+                Preprocess::Upscale { factor } => {
+                    let (width, height) = (img.width() as f32, img.height() as f32);
+                    img.resize_exact(
+                        (width * factor).round() as u32,
+                        (height * factor).round() as u32,
+                        FilterType::CatmullRom,
+                    )
+                }
+                Preprocess::Binarize { threshold } => {
+                    let mut luma = img.to_luma8();
+                    let threshold = threshold.unwrap_or_else(|| otsu(&luma));
+                    for pixel in luma.pixels_mut() {
+                        pixel.0[0] = if pixel.0[0] > threshold { 255 } else { 0 };
+                    }
+                    DynamicImage::ImageLuma8(luma)
+                }
+                Preprocess::Deskew => {
+                    let luma = img.to_luma8();
+                    let angle = skew(&luma);
+                    if angle == 0.0 {
+                        img
+                    } else {
+                        DynamicImage::ImageLuma8(rotate(&luma, -angle))
+                    }
+                }
+            }
+        }
+    }
+
+    fn mean_luma(luma: &GrayImage) -> f32 {
+        let sum = luma.pixels().map(|pixel| pixel.0[0] as u64).sum::<u64>();
+        sum as f32 / (luma.width() as u64 * luma.height() as u64).max(1) as f32
+    }
+
+    /// the threshold that best separates the histogram into two classes
+    fn otsu(luma: &GrayImage) -> u8 {
+        let mut histogram = [0u64; 256];
+        for pixel in luma.pixels() {
+            histogram[pixel.0[0] as usize] += 1;
+        }
+        let total = histogram.iter().sum::<u64>() as f64;
+        let sum = (0..256)
+            .map(|i| i as f64 * histogram[i] as f64)
+            .sum::<f64>();
+        let (mut weight, mut partial, mut best, mut threshold) = (0.0, 0.0, 0.0, 0);
+        for (i, count) in histogram.iter().enumerate() {
+            weight += *count as f64;
+            if weight == 0.0 || weight == total {
+                continue;
+            }
+            partial += i as f64 * *count as f64;
+            let (mean_b, mean_f) = (partial / weight, (sum - partial) / (total - weight));
+            let variance = weight * (total - weight) * (mean_b - mean_f).powi(2);
+            if variance > best {
+                (best, threshold) = (variance, i as u8);
+            }
+        }
+        threshold
+    }
+
+    /// the angle in radians of the text lines, found by the sharpest projection profile
+    fn skew(luma: &GrayImage) -> f32 {
+        let threshold = otsu(luma);
+        // text is assumed to be darker than the background
+        let ink = luma
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0[0] <= threshold)
+            .map(|(x, y, _)| (x as f32, y as f32))
+            .collect::<Vec<_>>();
+        let height = luma.height() as f32 + luma.width() as f32;
+        let mut best = (0.0, f32::MIN);
+        // from -5 to 5 degrees in steps of 0.25 degree
+        for step in -20..=20 {
+            let angle = (step as f32 * 0.25).to_radians();
+            let (sin, cos) = angle.sin_cos();
+            let mut profile = vec![0u32; height as usize * 2 + 1];
+            for (x, y) in ink.iter() {
+                let row = (y * cos - x * sin + height).round() as usize;
+                if let Some(count) = profile.get_mut(row) {
+                    *count += 1;
+                }
+            }
+            let sharpness = profile
+                .iter()
+                .map(|count| (*count as f32).powi(2))
+                .sum::<f32>();
+            if sharpness > best.1 {
+                best = (angle, sharpness);
+            }
+        }
+        best.0
+    }
+
+    /// rotate the image around its center, filling the uncovered area with white
+    fn rotate(luma: &GrayImage, angle: f32) -> GrayImage {
+        let (width, height) = luma.dimensions();
+        let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+        let (sin, cos) = angle.sin_cos();
+        GrayImage::from_fn(width, height, |x, y| {
+            let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+            let (sx, sy) = (dx * cos + dy * sin + cx, -dx * sin + dy * cos + cy);
+            if (0.0..width as f32).contains(&sx) && (0.0..height as f32).contains(&sy) {
+                *luma.get_pixel(sx as u32, sy as u32)
+            } else {
+                image::Luma([255])
+            }
+        })
+    }
 }
 #[cfg(feature = "ocr")]
 pub use ocr_impl::*;
+
+#[cfg(feature = "video")]
+mod video_impl {
+    use super::*;
+    use std::{process::Command, time::Duration};
+
+    /// how frames are picked out of a video
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub enum FrameSelect {
+        /// the keyframes as encoded in the video stream
+        Keyframes,
+        /// one frame every `millis` milliseconds, starting from the beginning
+        Interval { millis: u64 },
+    }
+
+    /// a thin wrapper around the `ffprobe` and `ffmpeg` executables
+    pub struct Ffmpeg;
+
+    impl Ffmpeg {
+        fn output(cmd: &mut Command) -> anyhow::Result<String> {
+            let output = cmd.output()?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "{:?} failed: {}",
+                    cmd.get_program(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )
+            }
+            Ok(String::from_utf8(output.stdout)?)
+        }
+        fn seconds(s: &str) -> Option<Duration> {
+            s.trim().parse::<f64>().ok().map(Duration::from_secs_f64)
+        }
+        /// the duration of the whole video
+        pub fn duration(src: &Path) -> anyhow::Result<Duration> {
+            let out = Self::output(
+                Command::new("ffprobe")
+                    .args(["-v", "error"])
+                    .args(["-show_entries", "format=duration"])
+                    .args(["-of", "csv=p=0"])
+                    .arg(src),
+            )?;
+            Self::seconds(&out).ok_or_else(|| anyhow::anyhow!("failed to probe video duration"))
+        }
+        /// the timestamps of all keyframes in the first video stream
+        pub fn keyframes(src: &Path) -> anyhow::Result<Vec<Duration>> {
+            let out = Self::output(
+                Command::new("ffprobe")
+                    .args(["-v", "error"])
+                    .args(["-select_streams", "v:0"])
+                    .args(["-skip_frame", "nokey"])
+                    .args(["-show_entries", "frame=pts_time"])
+                    .args(["-of", "csv=p=0"])
+                    .arg(src),
+            )?;
+            Ok(out.lines().filter_map(Self::seconds).collect())
+        }
+        /// the timestamps picked by the selection
+        pub fn select(src: &Path, select: &FrameSelect) -> anyhow::Result<Vec<Duration>> {
+            match select {
+                FrameSelect::Keyframes => Self::keyframes(src),
+                FrameSelect::Interval { millis: 0 } => {
+                    anyhow::bail!("frame interval must be positive")
+                }
+                FrameSelect::Interval { millis } => {
+                    let duration = Self::duration(src)?;
+                    let step = Duration::from_millis(*millis);
+                    Ok(
+                        std::iter::successors(Some(Duration::ZERO), |t| Some(*t + step))
+                            .take_while(|t| *t < duration)
+                            .collect(),
+                    )
+                }
+            }
+        }
+        /// extract the frame at the timestamp as an image file of the given extension
+        pub fn frame(src: &Path, at: Duration, ext: EntityExt) -> anyhow::Result<NamedTempFile> {
+            let temp = tempfile::Builder::new()
+                .suffix(&format!(".{}", ext))
+                .tempfile()?;
+            Self::output(
+                Command::new("ffmpeg")
+                    .args(["-v", "error", "-y"])
+                    .args(["-ss", &format!("{:.3}", at.as_secs_f64())])
+                    .arg("-i")
+                    .arg(src)
+                    .args(["-frames:v", "1"])
+                    .arg(temp.path()),
+            )?;
+            Ok(temp)
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct FrameVideo {
+        pub ext: EntityExt,
+        /// the timestamps of the frames in milliseconds, each resulting in a `Role::Nth`
+        pub millis: Vec<u64>,
+    }
+
+    impl Registrable for FrameVideo {
+        const NAME: &'static str = "Frames";
+        const ACCEPTS: &'static [EntityKind] = &[EntityKind::Video];
+        const EXT: EntityExt = EntityExt::Png;
+        fn operation() -> OperationKind {
+            OperationKind::Frame
+        }
+    }
+
+    impl FrameSelect {
+        pub fn validate(&self) -> Result<(), OperationError> {
+            match self {
+                FrameSelect::Interval { millis: 0 } => Err(OperationError::Invalid(
+                    "frame interval must be positive".to_string(),
+                )),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl FrameVideo {
+        /// ensure that all timestamps fall within the video
+        fn expect_within(&self, duration: Duration) -> Result<(), OperationError> {
+            match self
+                .millis
+                .iter()
+                .find(|millis| Duration::from_millis(**millis) >= duration)
+            {
+                Some(millis) => Err(OperationError::Invalid(format!(
+                    "frame at {}ms is beyond the {}ms video",
+                    millis,
+                    duration.as_millis()
+                ))),
+                None => Ok(()),
+            }
+        }
+    }
+
+    impl Operable for FrameVideo {
+        fn kind(&self) -> OperationKind {
+            OperationKind::Frame
+        }
+        fn validate(&self, base: &[MioId], mio: &Mio) -> Result<(), OperationError> {
+            expect_formats(base, mio, &[EntityExt::Mp4])?;
+            if !IMAGE_FORMATS.contains(&self.ext) {
+                return Err(OperationError::Format(self.ext));
+            }
+            if self.millis.is_empty() {
+                return Err(OperationError::Invalid("no frame is selected".to_string()));
+            }
+            for id in base {
+                let specter = mio
+                    .specterish(id)
+                    .map_err(|_| OperationError::Missing(*id))?;
+                // lazy bases can only be probed once actualized
+                if !specter.exists(&mio.dirs) {
+                    continue;
+                }
+                let src = specter
+                    .read_as_temp(&mio.dirs)
+                    .map_err(OperationError::Unreadable)?;
+                let duration = Ffmpeg::duration(src.path()).map_err(OperationError::Unreadable)?;
+                self.expect_within(duration)?;
+            }
+            Ok(())
+        }
+        fn outputs(&self) -> Vec<(Role, EntityExt)> {
+            (0..self.millis.len())
+                .map(|n| (Role::Nth(n), self.ext))
+                .collect()
+        }
+        fn execute(self, src: NamedTempFile) -> anyhow::Result<Vec<(Role, Vec<u8>)>> {
+            // lazy bases are only probed now
+            self.expect_within(Ffmpeg::duration(src.path())?)?;
+            let mut res = Vec::new();
+            for (n, millis) in self.millis.into_iter().enumerate() {
+                let frame = Ffmpeg::frame(src.path(), Duration::from_millis(millis), self.ext)?;
+                res.push((Role::Nth(n), std::fs::read(frame.path())?));
+            }
+            Ok(res)
+        }
+    }
+
+    #[cfg(feature = "ocr")]
+    #[derive(Serialize, Deserialize)]
+    pub struct OcrVideo {
+        pub ext: EntityExt,
+        pub lang: String,
+        pub select: FrameSelect,
+    }
+
+    #[cfg(feature = "ocr")]
+    impl Registrable for OcrVideo {
+        const NAME: &'static str = "Video to text";
+        const ACCEPTS: &'static [EntityKind] = &[EntityKind::Video];
+        const EXT: EntityExt = EntityExt::Txt;
+        fn operation() -> OperationKind {
+            OperationKind::As(EntityKind::Text)
+        }
+    }
+
+    #[cfg(feature = "ocr")]
+    impl Operable for OcrVideo {
+        fn kind(&self) -> OperationKind {
+            OperationKind::As(EntityKind::Text)
+        }
+        fn validate(&self, base: &[MioId], mio: &Mio) -> Result<(), OperationError> {
+            expect_formats(base, mio, &[EntityExt::Mp4])?;
+            expect_text(self.ext)?;
+            self.select.validate()?;
+            OcrText::expect_lang(&self.lang)
+        }
+        fn outputs(&self) -> Vec<(Role, EntityExt)> {
+            vec![(Role::Primary, self.ext)]
+        }
+        /// ocr each selected frame, keeping its timestamp and skipping repeated text
+        fn execute(self, src: NamedTempFile) -> anyhow::Result<Vec<(Role, Vec<u8>)>> {
+            let mut res = String::new();
+            let mut last = String::new();
+            for at in Ffmpeg::select(src.path(), &self.select)? {
+                let frame = Ffmpeg::frame(src.path(), at, EntityExt::Png)?;
+                let text = OcrText {
+                    lang: self.lang.clone(),
+                    ..Default::default()
+                }
+                .execute(frame)?
+                .into_iter()
+                .find_map(|(role, text)| (role == Role::Primary).then_some(text))
+                .unwrap_or_default();
+                let text = String::from_utf8(text)?.trim().to_string();
+                if text.is_empty() || text == last {
+                    continue;
+                }
+                let millis = at.as_millis();
+                res.push_str(&format!(
+                    "[{:02}:{:02}:{:02}.{:03}]\n{}\n\n",
+                    millis / 3_600_000,
+                    millis / 60_000 % 60,
+                    millis / 1_000 % 60,
+                    millis % 1_000,
+                    text
+                ));
+                last = text;
+            }
+            Ok(vec![(Role::Primary, res.into_bytes())])
+        }
+    }
+}
+#[cfg(feature = "video")]
+pub use video_impl::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_follow_modification() {
+        let digests = Digests::default();
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"mio").unwrap();
+        let first = digests.digest(file.path()).unwrap();
+        assert_eq!(first, Sha256::digest(b"mio").to_vec());
+        assert_eq!(digests.digest(file.path()).unwrap(), first);
+        file.write_all(b" ring").unwrap();
+        assert_eq!(
+            digests.digest(file.path()).unwrap(),
+            Sha256::digest(b"mio ring").to_vec()
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn image_attributes_are_validated() {
+        let (tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let path = tmp.path().join("mio.png");
+        image::RgbImage::new(4, 3).save(&path).unwrap();
+        let base = FileImport::new([&path]).interpret(&mut mio).unwrap();
+
+        let crop = |x, y, width, height| {
+            let attr = serde_json::json!({
+                "ext": "Png", "x": x, "y": y, "width": width, "height": height
+            });
+            CropImage::prepare(&attr, &base, &mio).map(|_| ())
+        };
+        assert!(crop(0, 0, 4, 3).is_ok());
+        assert!(crop(1, 1, 3, 2).is_ok());
+        for (x, y, width, height) in [
+            (1, 0, 4, 3),
+            (0, 1, 4, 3),
+            (0, 0, 0, 3),
+            (u32::MAX, 0, 2, 1),
+        ] {
+            assert!(matches!(
+                crop(x, y, width, height),
+                Err(OperationError::OutOfBounds { bounds: (4, 3), .. })
+            ));
+        }
+
+        let convert = |ext: &str, quality: Option<u8>| {
+            let attr = serde_json::json!({ "ext": ext, "quality": quality });
+            ConvertImage::prepare(&attr, &base, &mio).map(|_| ())
+        };
+        assert!(convert("Jpg", Some(1)).is_ok());
+        assert!(convert("Jpg", Some(100)).is_ok());
+        assert!(convert("Webp", None).is_ok());
+        for (ext, quality) in [("Jpg", 0), ("Jpg", 101), ("Webp", 80)] {
+            assert!(matches!(
+                convert(ext, Some(quality)),
+                Err(OperationError::Invalid(_))
+            ));
+        }
+        assert!(matches!(
+            convert("Mp3", None),
+            Err(OperationError::Format(EntityExt::Mp3))
+        ));
+    }
+
+    #[cfg(feature = "ocr")]
+    #[test]
+    fn ocr_box_within() {
+        let bx = |left, top, width, height| OcrBox {
+            left,
+            top,
+            width,
+            height,
+        };
+        let area = bx(10, 10, 100, 50);
+        assert!(bx(20, 20, 10, 10).within(&area));
+        // only the center counts
+        assert!(bx(0, 0, 30, 30).within(&area));
+        assert!(!bx(100, 50, 30, 30).within(&area));
+        // the far edges are exclusive
+        assert!(!bx(110, 20, 0, 0).within(&area));
+        // boxes at the end of the coordinates don't overflow
+        let edge = bx(u32::MAX - 2, u32::MAX - 2, u32::MAX, u32::MAX);
+        assert!(bx(u32::MAX - 1, u32::MAX - 1, 0, 0).within(&edge));
+        assert!(!bx(u32::MAX, u32::MAX, u32::MAX, u32::MAX).within(&edge));
+    }
+}
@@ -1,30 +1,389 @@
 use super::*;
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+/// the trimmed stdout of a command, if it succeeds with any
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let output = String::from_utf8(output.stdout)
+        .ok()
+        .filter(|_| output.status.success())?;
+    Some(output.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// like `output`, but fails if the command can't be run or doesn't finish in time
+fn output_within(
+    program: &str,
+    args: &[&str],
+    timeout: Duration,
+) -> anyhow::Result<Option<String>> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let deadline = Instant::now() + timeout;
+    while child.try_wait()?.is_none() {
+        if Instant::now() > deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("{} timed out", program)
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let output = child.wait_with_output()?;
+    let output = String::from_utf8(output.stdout)
+        .ok()
+        .filter(|_| output.status.success());
+    Ok(output
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty()))
+}
+
+/// how long to wait for the focus, e.g. while macOS asks whether to allow the automation
+const FOCUS_TIMEOUT: Duration = Duration::from_millis(500);
+/// set once the focus can't be told at all, so that later registrations don't wait for it again
+static FOCUS_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+impl Metadata {
+    /// the hostname, looked up once
+    pub fn hostname() -> Option<String> {
+        static HOSTNAME: OnceLock<Option<String>> = OnceLock::new();
+        HOSTNAME
+            .get_or_init(|| {
+                std::env::var("HOSTNAME")
+                    .ok()
+                    .or_else(|| std::env::var("COMPUTERNAME").ok())
+                    .or_else(|| output("hostname", &[]))
+            })
+            .clone()
+    }
+
+    /// the focused application and the title of its window, where the platform tells;
+    /// the focus is no longer asked for once the platform fails to answer in time
+    pub fn focused() -> (Option<String>, Option<String>) {
+        if FOCUS_UNAVAILABLE.load(Ordering::Relaxed) {
+            return (None, None);
+        }
+        let focused = if cfg!(target_os = "macos") {
+            let script = [
+                "tell application \"System Events\"",
+                "set proc to first process whose frontmost is true",
+                "set title to \"\"",
+                "try",
+                "set title to name of front window of proc",
+                "end try",
+                "return (name of proc) & linefeed & title",
+                "end tell",
+            ];
+            let args = script
+                .into_iter()
+                .flat_map(|line| ["-e", line])
+                .collect_vec();
+            output_within("osascript", &args, FOCUS_TIMEOUT).map(|out| {
+                let out = out.unwrap_or_default();
+                let mut lines = out.lines().map(str::trim).filter(|s| !s.is_empty());
+                (
+                    lines.next().map(String::from),
+                    lines.next().map(String::from),
+                )
+            })
+        } else if cfg!(target_os = "linux") {
+            // xdotool only speaks x11
+            if std::env::var_os("DISPLAY").is_none() {
+                return (None, None);
+            }
+            let args = ["getactivewindow", "getwindowpid", "getwindowname"];
+            output_within("xdotool", &args, FOCUS_TIMEOUT).map(|out| {
+                let out = out.unwrap_or_default();
+                let mut lines = out.lines();
+                let application = lines
+                    .next()
+                    .and_then(|pid| fs::read_to_string(format!("/proc/{}/comm", pid.trim())).ok())
+                    .map(|comm| comm.trim().to_string());
+                let window = lines.next().map(str::trim).filter(|s| !s.is_empty());
+                (application, window.map(String::from))
+            })
+        } else {
+            return (None, None);
+        };
+        focused.unwrap_or_else(|e| {
+            log::warn!("failed to tell the focus, no longer asking for it: {:#}", e);
+            FOCUS_UNAVAILABLE.store(true, Ordering::Relaxed);
+            (None, None)
+        })
+    }
+
+    /// the common metadata of the moment, with the focus only for sources it tells about
+    pub fn context(source: Source) -> Self {
+        let (application, window) = if source.focused() {
+            Metadata::focused()
+        } else {
+            (None, None)
+        };
+        Metadata {
+            application,
+            window,
+            hostname: Metadata::hostname(),
+            ..Default::default()
+        }
+    }
+
+    /// fill in the common metadata not given by the persister
+    fn complete(&mut self, source: Source, context: &Metadata) {
+        self.source.get_or_insert(source);
+        if source.focused() && self.application.is_none() && self.window.is_none() {
+            self.application.clone_from(&context.application);
+            self.window.clone_from(&context.window);
+        }
+        if self.hostname.is_none() {
+            self.hostname.clone_from(&context.hostname);
+        }
+    }
+}
 
 impl Mio {
+    /// the metadata of a registered entity
+    pub fn metadata(&self, id: &MioId) -> Option<&Metadata> {
+        self.ring
+            .entities
+            .get(id)
+            .map(|entity| &entity.body.metadata)
+    }
+
+    /// the moments of the entities whose metadata satisfies the predicate, in chronological order
+    pub fn captured_where(&self, predicate: impl Fn(&Metadata) -> bool) -> Vec<Ephemerality> {
+        self.chronology
+            .iter()
+            .filter(|e| self.metadata(&e.base).is_some_and(&predicate))
+            .collect()
+    }
+
     /// run a persistable and memorize its entities into the mio ring
     fn register(&mut self, persister: &impl Persistable) -> anyhow::Result<Vec<MioId>> {
+        // the focus is taken before the capture could steal it
+        let context = Metadata::context(persister.source());
+        let persisted = persister.persist()?;
+        self.memorize(persister.source(), &context, persisted)
+    }
+
+    /// memorize persisted files as entities into the mio ring
+    fn memorize(
+        &mut self,
+        source: Source,
+        context: &Metadata,
+        persisted: Vec<(NamedTempFile, EntityExt, Metadata)>,
+    ) -> anyhow::Result<Vec<MioId>> {
         let mut ids = Vec::new();
-        for (src, ext) in persister.persist()? {
-            let id = self.alloc.allocate().into();
-            let entity = Specter {
-                id,
-                ext,
-                nonce: Specter::<Concrete>::gen_nouce(),
-                deps: Vec::new(),
-                body: Concrete {
-                    pool: self.alloc.allocate_pool(POOL_SIZE),
-                    providence: Providence::Registered,
-                },
-            };
-            self.chronology.push(Ephemerality {
-                time: SystemTime::now(),
-                base: id,
-            });
-            entity
-                .ring_and(&mut self.ring)?
-                .replace(&self.dirs, src.path())?;
-            ids.push(id);
+        for (src, ext, metadata) in persisted {
+            ids.push(self.inscribe(source, context, src.path(), ext, metadata)?);
         }
+        self.trigger(source, &ids);
+        Ok(ids)
+    }
+
+    /// memorize a file as an entity into the mio ring, without triggering the pipelines
+    fn inscribe(
+        &mut self,
+        source: Source,
+        context: &Metadata,
+        src: &Path,
+        ext: EntityExt,
+        mut metadata: Metadata,
+    ) -> anyhow::Result<MioId> {
+        metadata.complete(source, context);
+        let time = metadata.modified.unwrap_or_else(SystemTime::now);
+        let id = self.alloc.allocate().into();
+        let mut entity = Specter {
+            id,
+            ext,
+            nonce: Specter::<Concrete>::gen_nouce(),
+            deps: Vec::new(),
+            body: Concrete {
+                pool: self.alloc.allocate_pool(POOL_SIZE),
+                providence: Providence::Registered,
+                metadata,
+            },
+        };
+        // the file is written first so that no entity is indexed without one
+        entity.replace(&self.dirs, src)?;
+        entity.ring_and(&mut self.ring)?;
+        self.chronology.insert(Ephemerality {
+            time,
+            base: id,
+            event: Event::Captured,
+        });
+        Ok(id)
+    }
+
+    /// import a file and memorize it at once, unless it's of an unknown type;
+    /// the pipelines are left for the caller to trigger
+    pub(crate) fn import(
+        &mut self,
+        path: &Path,
+        context: &Metadata,
+    ) -> anyhow::Result<Option<MioId>> {
+        let Some((src, ext, metadata)) = FileImport::import(path)? else {
+            return Ok(None);
+        };
+        let id = self.inscribe(Source::Import, context, src.path(), ext, metadata)?;
+        Ok(Some(id))
+    }
+}
+
+/// existing files, or the files within directories
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileImport {
+    pub paths: Vec<PathBuf>,
+    /// whether to descend into the subdirectories
+    #[serde(default)]
+    pub recursive: bool,
+    /// glob patterns of the paths, relative to the imported directory, to be imported;
+    /// all are imported if empty
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// glob patterns of the paths not to be imported
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl FileImport {
+    pub fn new(paths: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            paths: paths.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// all files to be imported, in a stable order
+    pub fn files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let patterns = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| glob::Pattern::new(glob))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let (include, exclude) = (patterns(&self.include)?, patterns(&self.exclude)?);
+        let wanted = |rel: &Path| {
+            (include.is_empty() || include.iter().any(|p| p.matches_path(rel)))
+                && !exclude.iter().any(|p| p.matches_path(rel))
+        };
+
+        let mut files = Vec::new();
+        for root in self.paths.iter() {
+            if root.is_file() {
+                files.push(root.clone());
+                continue;
+            }
+            let mut dirs = vec![root.clone()];
+            while let Some(dir) = dirs.pop() {
+                let mut entries = fs::read_dir(&dir)?
+                    .map(|entry| {
+                        let entry = entry?;
+                        Ok((entry.path(), entry.file_type()?))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (path, file_type) in entries {
+                    // symlinked directories are never followed, as they may form cycles
+                    if file_type.is_dir() {
+                        if self.recursive {
+                            dirs.push(path);
+                        }
+                    } else if path.is_file() && wanted(path.strip_prefix(root).unwrap_or(&path)) {
+                        files.push(path);
+                    }
+                }
+            }
+        }
+        Ok(files)
+    }
+}
+
+impl Persistable for FileImport {
+    fn source(&self) -> Source {
+        Source::Import
+    }
+    /// a file that fails to be imported is skipped, without failing the others
+    fn persist(&self) -> anyhow::Result<Vec<(NamedTempFile, EntityExt, Metadata)>> {
+        let mut v = Vec::new();
+        for path in self.files()? {
+            match Self::import(&path) {
+                Ok(Some(imported)) => v.push(imported),
+                Ok(None) => log::warn!("skipping file of unknown type: {}", path.display()),
+                Err(e) => log::warn!("failed to import {}: {:#}", path.display(), e),
+            }
+        }
+        Ok(v)
+    }
+}
+
+/// how much of a file is enough to tell binary formats apart
+const SNIFF_LEN: u64 = 64 * 1024;
+
+/// sniff the file by its head, reading all of it only when it looks like text
+fn sniff_file(path: &Path) -> anyhow::Result<Option<EntityExt>> {
+    let mut head = Vec::new();
+    fs::File::open(path)?
+        .take(SNIFF_LEN)
+        .read_to_end(&mut head)?;
+    if (head.len() as u64) < SNIFF_LEN {
+        return Ok(EntityExt::sniff(&head));
+    }
+    // a character may be cut at the end of the head
+    let valid = match std::str::from_utf8(&head) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => head.len(),
+    };
+    match EntityExt::sniff(&head[..valid]) {
+        Some(ext) if ext.kind() == EntityKind::Text => Ok(EntityExt::sniff(&fs::read(path)?)),
+        ext => Ok(ext),
+    }
+}
+
+impl FileImport {
+    /// copy the file aside as it is now, unless it's of an unknown type
+    fn import(path: &Path) -> anyhow::Result<Option<(NamedTempFile, EntityExt, Metadata)>> {
+        let modified = fs::metadata(path)?.modified().ok();
+        let file = NamedTempFile::new()?;
+        fs::copy(path, file.path())?;
+        let Some(ext) = sniff_file(file.path())? else {
+            return Ok(None);
+        };
+        let metadata = Metadata {
+            content: Some(ext.kind()),
+            mime: Some(ext.mime().to_string()),
+            modified,
+            origin: Some(path.canonicalize().unwrap_or_else(|_| path.to_path_buf())),
+            ..Default::default()
+        };
+        Ok(Some((file, ext, metadata)))
+    }
+}
+
+impl Interpretable for FileImport {
+    type Mio<'a> = &'a mut Mio;
+    type Target<'a> = Vec<MioId>;
+    /// each file is memorized before the next is read, so that only one is held aside at a time
+    fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
+        let context = Metadata::context(Source::Import);
+        let mut ids = Vec::new();
+        for path in self.files()? {
+            match mio.import(&path, &context) {
+                Ok(Some(id)) => ids.push(id),
+                Ok(None) => log::warn!("skipping file of unknown type: {}", path.display()),
+                Err(e) => log::warn!("failed to import {}: {:#}", path.display(), e),
+            }
+        }
+        mio.trigger(Source::Import, &ids);
         Ok(ids)
     }
 }
@@ -33,31 +392,420 @@ impl Mio {
 mod screenshot_impl {
     use super::*;
 
-    pub struct ScreenShot;
+    use screenshots::Screen;
+    use std::time::{Duration, Instant};
+
+    /// what to capture
+    #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub enum ScreenShot {
+        /// every display, each as an entity
+        #[default]
+        All,
+        /// every display, stitched into one image by their layout
+        Composite,
+        /// the display of the id
+        Display(u32),
+        /// the display under the mouse cursor
+        Cursor,
+        /// a rectangle in global coordinates, which may span several displays
+        Region {
+            x: i32,
+            y: i32,
+            width: u32,
+            height: u32,
+        },
+        /// the first window whose title contains the string;
+        /// on macos, the front window of the first application whose name contains it
+        Window(String),
+    }
+
+    /// the `KEY=value` lines printed by `xdotool --shell`
+    fn shell_var(vars: &str, key: &str) -> anyhow::Result<i64> {
+        vars.lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .ok_or_else(|| anyhow::anyhow!("missing {} from xdotool", key))?
+            .parse()
+            .map_err(Into::into)
+    }
+
+    /// the position of the mouse cursor in global coordinates
+    fn cursor() -> anyhow::Result<(i32, i32)> {
+        if cfg!(target_os = "macos") {
+            // cocoa counts from the bottom of the main display
+            let script = "ObjC.import('AppKit'); \
+                var p = $.NSEvent.mouseLocation; \
+                var h = $.NSScreen.screens.objectAtIndex(0).frame.size.height; \
+                Math.round(p.x) + ',' + Math.round(h - p.y)";
+            let position = output("osascript", &["-l", "JavaScript", "-e", script])
+                .ok_or_else(|| anyhow::anyhow!("failed to locate the cursor"))?;
+            let (x, y) = position
+                .split_once(',')
+                .ok_or_else(|| anyhow::anyhow!("unexpected cursor position {}", position))?;
+            Ok((x.trim().parse()?, y.trim().parse()?))
+        } else if cfg!(target_os = "linux") {
+            let vars = output("xdotool", &["getmouselocation", "--shell"])
+                .ok_or_else(|| anyhow::anyhow!("failed to locate the cursor"))?;
+            Ok((shell_var(&vars, "X")? as i32, shell_var(&vars, "Y")? as i32))
+        } else {
+            anyhow::bail!("locating the cursor is not supported on this platform")
+        }
+    }
+
+    /// escape the posix extended regex metacharacters, which `xdotool search` matches against
+    pub(super) fn escape_ere(s: &str) -> String {
+        let mut escaped = String::with_capacity(s.len());
+        for c in s.chars() {
+            if r".[]{}()\*+?^$|".contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+
+    /// the geometry of the window in global coordinates
+    fn window(name: &str) -> anyhow::Result<(i32, i32, u32, u32)> {
+        let not_found = || anyhow::anyhow!("window {} not found", name);
+        if cfg!(target_os = "macos") {
+            let script = format!(
+                "tell application \"System Events\" to get {{position, size}} of front window of \
+                (first process whose name contains \"{}\")",
+                name.replace('"', "\\\"")
+            );
+            let geometry = output("osascript", &["-e", &script]).ok_or_else(not_found)?;
+            let geometry = geometry
+                .split(',')
+                .map(|n| n.trim().parse::<i32>())
+                .collect::<Result<Vec<_>, _>>()?;
+            let [x, y, width, height] = geometry[..] else {
+                anyhow::bail!("unexpected geometry of window {}", name)
+            };
+            Ok((x, y, width.max(0) as u32, height.max(0) as u32))
+        } else if cfg!(target_os = "linux") {
+            let pattern = escape_ere(name);
+            let windows = output("xdotool", &["search", "--onlyvisible", "--name", &pattern])
+                .ok_or_else(not_found)?;
+            let window = windows.lines().next().ok_or_else(not_found)?;
+            let vars = output("xdotool", &["getwindowgeometry", "--shell", window])
+                .ok_or_else(not_found)?;
+            Ok((
+                shell_var(&vars, "X")? as i32,
+                shell_var(&vars, "Y")? as i32,
+                shell_var(&vars, "WIDTH")? as u32,
+                shell_var(&vars, "HEIGHT")? as u32,
+            ))
+        } else {
+            anyhow::bail!("capturing windows is not supported on this platform")
+        }
+    }
+
+    /// capture the screen, or the area relative to it
+    fn capture(
+        screen: &Screen,
+        area: Option<(i32, i32, u32, u32)>,
+    ) -> anyhow::Result<(NamedTempFile, EntityExt, Metadata)> {
+        let image = match area {
+            Some((x, y, width, height)) => screen.capture_area(x, y, width, height)?,
+            None => screen.capture()?,
+        };
+        let mut file = NamedTempFile::new()?;
+        image.write_to(&mut file, image::ImageOutputFormat::Png)?;
+        let metadata = Metadata {
+            content: Some(EntityKind::Image),
+            display: Some(monitor(screen)),
+            ..Default::default()
+        };
+        Ok((file, EntityExt::Png, metadata))
+    }
+
+    pub(super) fn monitor(screen: &Screen) -> Monitor {
+        let info = screen.display_info;
+        Monitor {
+            id: info.id,
+            x: info.x,
+            y: info.y,
+            width: info.width,
+            height: info.height,
+            scale: info.scale_factor,
+            primary: info.is_primary,
+        }
+    }
+
+    /// capture a rectangle in global coordinates; the parts on different displays are
+    /// stitched by their layout, at the finest scale among them
+    fn region(
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> anyhow::Result<(NamedTempFile, EntityExt, Metadata)> {
+        use image::{imageops, RgbaImage};
+        anyhow::ensure!(width > 0 && height > 0, "empty region");
+        let (right, bottom) = (x as i64 + width as i64, y as i64 + height as i64);
+        // the intersection of the region with each display
+        let parts = Screen::all()?
+            .into_iter()
+            .filter_map(|screen| {
+                let info = screen.display_info;
+                let (left, top) = (x.max(info.x), y.max(info.y));
+                let width = right.min(info.x as i64 + info.width as i64) - left as i64;
+                let height = bottom.min(info.y as i64 + info.height as i64) - top as i64;
+                (width > 0 && height > 0).then_some((
+                    screen,
+                    left,
+                    top,
+                    width as u32,
+                    height as u32,
+                ))
+            })
+            .collect_vec();
+        match &parts[..] {
+            [] => anyhow::bail!("region is off all displays"),
+            [(screen, left, top, width, height)] => {
+                let info = screen.display_info;
+                return capture(screen, Some((left - info.x, top - info.y, *width, *height)));
+            }
+            _ => {}
+        }
+
+        let scale = parts
+            .iter()
+            .map(|(screen, ..)| screen.display_info.scale_factor)
+            .fold(1.0, f32::max);
+        let scaled = |len: i64| (len as f32 * scale).round() as i64;
+        let mut canvas = RgbaImage::new(scaled(width as i64) as u32, scaled(height as i64) as u32);
+        for (screen, left, top, width, height) in parts {
+            let info = screen.display_info;
+            let mut part = screen.capture_area(left - info.x, top - info.y, width, height)?;
+            let size = (scaled(width as i64) as u32, scaled(height as i64) as u32);
+            if part.dimensions() != size {
+                part = imageops::resize(&part, size.0, size.1, imageops::FilterType::Lanczos3);
+            }
+            imageops::overlay(
+                &mut canvas,
+                &part,
+                scaled(left as i64 - x as i64),
+                scaled(top as i64 - y as i64),
+            );
+        }
+        let mut file = NamedTempFile::new()?;
+        canvas.write_to(&mut file, image::ImageOutputFormat::Png)?;
+        let metadata = Metadata {
+            content: Some(EntityKind::Image),
+            ..Default::default()
+        };
+        Ok((file, EntityExt::Png, metadata))
+    }
+
+    /// the bounding rectangle of all displays in global coordinates
+    fn bounds() -> anyhow::Result<(i32, i32, u32, u32)> {
+        let infos = Screen::all()?
+            .into_iter()
+            .map(|screen| screen.display_info)
+            .collect_vec();
+        let left = infos.iter().map(|info| info.x).min();
+        let top = infos.iter().map(|info| info.y).min();
+        let right = infos
+            .iter()
+            .map(|info| info.x as i64 + info.width as i64)
+            .max();
+        let bottom = infos
+            .iter()
+            .map(|info| info.y as i64 + info.height as i64)
+            .max();
+        let (Some(left), Some(top), Some(right), Some(bottom)) = (left, top, right, bottom) else {
+            anyhow::bail!("no display found")
+        };
+        Ok((
+            left,
+            top,
+            (right - left as i64) as u32,
+            (bottom - top as i64) as u32,
+        ))
+    }
 
     impl Persistable for ScreenShot {
-        fn persist(&self) -> anyhow::Result<Vec<(NamedTempFile, EntityExt)>> {
-            let mut v = Vec::new();
-            for screen in screenshots::Screen::all()?.into_iter() {
-                let image = screen.capture()?;
-                let mut file = NamedTempFile::new()?;
-                image.write_to(&mut file, image::ImageOutputFormat::Png)?;
-                v.push((file, EntityExt::Png));
+        fn source(&self) -> Source {
+            Source::ScreenShot
+        }
+        fn persist(&self) -> anyhow::Result<Vec<(NamedTempFile, EntityExt, Metadata)>> {
+            match self {
+                ScreenShot::All => Screen::all()?
+                    .iter()
+                    .map(|screen| capture(screen, None))
+                    .collect(),
+                ScreenShot::Composite => {
+                    let (x, y, width, height) = bounds()?;
+                    Ok(vec![region(x, y, width, height)?])
+                }
+                ScreenShot::Display(id) => {
+                    let screen = Screen::all()?
+                        .into_iter()
+                        .find(|screen| screen.display_info.id == *id)
+                        .ok_or_else(|| anyhow::anyhow!("display {} not found", id))?;
+                    Ok(vec![capture(&screen, None)?])
+                }
+                ScreenShot::Cursor => {
+                    let (x, y) = cursor()?;
+                    Ok(vec![capture(&Screen::from_point(x, y)?, None)?])
+                }
+                ScreenShot::Region {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => Ok(vec![region(*x, *y, *width, *height)?]),
+                ScreenShot::Window(name) => {
+                    let (x, y, width, height) = window(name)?;
+                    Ok(vec![region(x, y, width, height)?])
+                }
             }
-            Ok(v)
         }
     }
 
     impl Interpretable for ScreenShot {
         type Mio<'a> = &'a mut Mio;
         type Target<'a> = Vec<MioId>;
-        fn interpret<'a>(mut self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
-            mio.register(&mut self)
+        fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
+            mio.register(&self)
+        }
+    }
+
+    /// how long the user has been idle, where the platform tells;
+    /// it takes `xprintidle` on linux, and is warned about once if missing
+    fn idle() -> Option<Duration> {
+        if cfg!(target_os = "macos") {
+            let ioreg = output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?;
+            let nanos = ioreg
+                .lines()
+                .find_map(|line| line.split_once("\"HIDIdleTime\" = "))?
+                .1
+                .trim()
+                .parse()
+                .ok()?;
+            Some(Duration::from_nanos(nanos))
+        } else if cfg!(target_os = "linux") {
+            let millis = output("xprintidle", &[]).and_then(|out| out.parse().ok());
+            if millis.is_none() {
+                static WARNED: std::sync::Once = std::sync::Once::new();
+                WARNED.call_once(|| {
+                    log::warn!(
+                        "can't tell how long the user has been idle, is xprintidle installed?"
+                    )
+                });
+            }
+            Some(Duration::from_millis(millis?))
+        } else {
+            None
+        }
+    }
+
+    /// the difference hash of the image, which barely changes unless the content does
+    pub(super) fn dhash(image: &image::DynamicImage) -> u64 {
+        let small = image
+            .resize_exact(9, 8, image::imageops::FilterType::Triangle)
+            .to_luma8();
+        (0..8)
+            .flat_map(|y| (0..8).map(move |x| (x, y)))
+            .fold(0, |hash, (x, y)| {
+                hash << 1 | (small.get_pixel(x, y).0[0] < small.get_pixel(x + 1, y).0[0]) as u64
+            })
+    }
+
+    /// screenshots taken on a schedule, stored as `periodic.json` in the config dir;
+    /// telling whether the user is away takes `xprintidle` on linux
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct PeriodicCapture {
+        /// minutes between captures; none are taken if zero
+        pub interval: u64,
+        /// seconds without input after which the user is considered away
+        pub idle: u64,
+        /// the most bits the perceptual hashes may differ by for the screen to count as unchanged
+        pub threshold: u32,
+        pub shot: ScreenShot,
+    }
+
+    impl Default for PeriodicCapture {
+        fn default() -> Self {
+            Self {
+                interval: 0,
+                idle: 5 * 60,
+                threshold: 4,
+                shot: ScreenShot::All,
+            }
+        }
+    }
+
+    impl PeriodicCapture {
+        pub fn path(dirs: &MioDirs) -> PathBuf {
+            dirs.config_dir.join("periodic.json")
+        }
+
+        pub fn read(dirs: &MioDirs) -> anyhow::Result<Self> {
+            let path = Self::path(dirs);
+            if !path.exists() {
+                return Ok(Self::default());
+            }
+            Ok(serde_json::from_slice(&fs::read(path)?)?)
+        }
+    }
+
+    /// registers a screenshot marked as automatic whenever one is due, to be polled regularly
+    pub struct PeriodicCapturer {
+        config: PeriodicCapture,
+        /// when the last capture was due
+        last: Option<Instant>,
+        /// the hashes of the last capture registered
+        pub(super) hashes: Vec<u64>,
+    }
+
+    impl PeriodicCapturer {
+        pub fn new(config: PeriodicCapture) -> Self {
+            Self {
+                config,
+                last: None,
+                hashes: Vec::new(),
+            }
+        }
+
+        /// register a screenshot if one is due, the user is around and the screen has changed
+        pub fn poll(&mut self, mio: &mut Mio) -> anyhow::Result<Vec<MioId>> {
+            let interval = Duration::from_secs(self.config.interval * 60);
+            if interval.is_zero() || self.last.is_some_and(|last| last.elapsed() < interval) {
+                return Ok(Vec::new());
+            }
+            self.last = Some(Instant::now());
+            // the user is assumed to be around if the platform doesn't tell
+            if idle().is_some_and(|idle| idle >= Duration::from_secs(self.config.idle)) {
+                return Ok(Vec::new());
+            }
+            let context = Metadata::context(Source::ScreenShot);
+            let mut persisted = self.config.shot.persist()?;
+            let hashes = persisted
+                .iter()
+                .map(|(file, _, _)| Ok(dhash(&image::open(file.path())?)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            if persisted.is_empty() || self.unchanged(&hashes) {
+                log::info!("skipping periodic capture of an unchanged screen");
+                return Ok(Vec::new());
+            }
+            self.hashes = hashes;
+            for (_, _, metadata) in persisted.iter_mut() {
+                metadata.automatic = true;
+            }
+            mio.memorize(Source::ScreenShot, &context, persisted)
+        }
+
+        /// whether the hashes are those of the last capture registered, up to the threshold
+        pub(super) fn unchanged(&self, hashes: &[u64]) -> bool {
+            hashes.len() == self.hashes.len()
+                && (hashes.iter().zip(self.hashes.iter()))
+                    .all(|(a, b)| (a ^ b).count_ones() <= self.config.threshold)
         }
     }
 }
 #[cfg(feature = "screenshot")]
-pub use screenshot_impl::ScreenShot;
+pub use screenshot_impl::{PeriodicCapture, PeriodicCapturer, ScreenShot};
 
 #[cfg(feature = "clipboard")]
 mod clipboard_impl {
@@ -82,13 +830,85 @@ mod clipboard_impl {
         }
     }
 
+    /// whether the text is nothing but a url with a scheme
+    pub(super) fn is_url(text: &str) -> bool {
+        let text = text.trim();
+        let Some((scheme, rest)) = text.split_once("://") else {
+            return false;
+        };
+        let mut scheme = scheme.chars();
+        scheme.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && scheme.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+            && !rest.is_empty()
+            && !text.contains(char::is_whitespace)
+    }
+
+    /// the types that password managers and other apps mark sensitive or transient copies with
+    const CONCEALED_TYPES: &[&str] = &[
+        "org.nspasteboard.ConcealedType",
+        "org.nspasteboard.TransientType",
+        "x-kde-passwordManagerHint",
+    ];
+
+    /// the types the clipboard currently offers, where the platform tells;
+    /// linux needs `xclip`, or `wl-paste` under wayland
+    fn offered_types() -> Vec<String> {
+        let types = if cfg!(target_os = "macos") {
+            let script = "ObjC.import('AppKit'); \
+                $.NSPasteboard.generalPasteboard.types.js.map(t => t.js).join('\\n')";
+            output("osascript", &["-l", "JavaScript", "-e", script])
+        } else if cfg!(target_os = "linux") {
+            if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                output("wl-paste", &["--list-types"])
+            } else {
+                output("xclip", &["-selection", "clipboard", "-t", "TARGETS", "-o"])
+            }
+        } else {
+            None
+        };
+        types
+            .map(|types| types.lines().map(|t| t.trim().to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    /// whether the copy is marked as a secret or as not meant to be kept
+    pub(super) fn concealed(types: &[String]) -> bool {
+        types.iter().any(|t| CONCEALED_TYPES.contains(&t.as_str()))
+    }
+
     impl Persistable for Clipboard {
-        fn persist(&self) -> anyhow::Result<Vec<(NamedTempFile, EntityExt)>> {
+        fn source(&self) -> Source {
+            Source::Clipboard
+        }
+        fn persist(&self) -> anyhow::Result<Vec<(NamedTempFile, EntityExt, Metadata)>> {
             let mut board = self.board.borrow_mut();
-            if let Ok(contents) = board.get_text() {
+            let metadata = |kind, mime: &str| Metadata {
+                content: Some(kind),
+                mime: Some(mime.to_string()),
+                ..Default::default()
+            };
+            let text = |contents: &str, ext| -> anyhow::Result<_> {
                 let mut file = NamedTempFile::new()?;
                 file.write_all(contents.as_bytes())?;
-                Ok(vec![(file, EntityExt::Txt)])
+                Ok((file, ext))
+            };
+            if let Ok(paths) = board.get().file_list() {
+                // sniffed as imports are, with copied directories giving the files within them
+                let mut persisted = FileImport::new(paths).persist()?;
+                for (_, _, metadata) in persisted.iter_mut() {
+                    metadata.mime = Some("text/uri-list".to_string());
+                }
+                Ok(persisted)
+            } else if let Ok(contents) = board.get().html() {
+                let (file, ext) = text(&contents, EntityExt::Html)?;
+                Ok(vec![(file, ext, metadata(EntityKind::Text, "text/html"))])
+            } else if let Ok(contents) = board.get_text() {
+                let (file, ext) = if is_url(&contents) {
+                    text(contents.trim(), EntityExt::Url)?
+                } else {
+                    text(&contents, EntityExt::Txt)?
+                };
+                Ok(vec![(file, ext, metadata(EntityKind::Text, "text/plain"))])
             } else if let Ok(contents) = board.get_image() {
                 use image::{DynamicImage, ImageBuffer, ImageOutputFormat};
                 let mut file = NamedTempFile::new()?;
@@ -99,7 +919,11 @@ mod clipboard_impl {
                 )
                 .ok_or_else(|| anyhow::anyhow!("failed to decode clipboard image"))?;
                 DynamicImage::ImageRgba8(buf).write_to(&mut file, ImageOutputFormat::Png)?;
-                Ok(vec![(file, EntityExt::Png)])
+                Ok(vec![(
+                    file,
+                    EntityExt::Png,
+                    metadata(EntityKind::Image, "image/png"),
+                )])
             } else {
                 log::warn!("clipboard is empty or has unsupported content");
                 Ok(Vec::new())
@@ -110,10 +934,1045 @@ mod clipboard_impl {
     impl Interpretable for Clipboard {
         type Mio<'a> = &'a mut Mio;
         type Target<'a> = Vec<MioId>;
-        fn interpret<'a>(mut self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
-            mio.register(&mut self)
+        fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
+            mio.register(&self)
+        }
+    }
+
+    /// what the clipboard watcher should never record, stored as `clipboard.json` in the config dir
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ClipboardExclusion {
+        /// applications whose copies are skipped, matched case-insensitively by substring
+        pub applications: Vec<String>,
+        /// regular expressions of text to be skipped
+        pub patterns: Vec<String>,
+    }
+
+    impl Default for ClipboardExclusion {
+        fn default() -> Self {
+            let managers = [
+                "1password",
+                "bitwarden",
+                "dashlane",
+                "enpass",
+                "keepass",
+                "keychain",
+                "lastpass",
+                "proton pass",
+            ];
+            Self {
+                applications: managers.into_iter().map(String::from).collect(),
+                patterns: Vec::new(),
+            }
+        }
+    }
+
+    impl ClipboardExclusion {
+        pub fn path(dirs: &MioDirs) -> PathBuf {
+            dirs.config_dir.join("clipboard.json")
+        }
+
+        pub fn read(dirs: &MioDirs) -> anyhow::Result<Self> {
+            let path = Self::path(dirs);
+            if !path.exists() {
+                return Ok(Self::default());
+            }
+            Ok(serde_json::from_slice(&fs::read(path)?)?)
+        }
+    }
+
+    /// registers each new clipboard item, to be polled whenever the clipboard changes
+    pub struct ClipboardWatcher {
+        clipboard: Clipboard,
+        applications: Vec<String>,
+        patterns: Vec<regex::Regex>,
+        /// the hash of the last item seen, recorded or not
+        last: Option<Vec<u8>>,
+    }
+
+    impl ClipboardWatcher {
+        pub fn new(exclusion: ClipboardExclusion) -> anyhow::Result<Self> {
+            Ok(Self {
+                clipboard: Clipboard::new()?,
+                applications: exclusion
+                    .applications
+                    .iter()
+                    .map(|app| app.to_lowercase())
+                    .collect(),
+                patterns: exclusion
+                    .patterns
+                    .iter()
+                    .map(|pattern| regex::Regex::new(pattern))
+                    .collect::<Result<_, _>>()?,
+                last: None,
+            })
+        }
+
+        fn excluded(
+            &self,
+            context: &Metadata,
+            persisted: &[(NamedTempFile, EntityExt, Metadata)],
+        ) -> bool {
+            let application = context
+                .application
+                .as_deref()
+                .unwrap_or_default()
+                .to_lowercase();
+            if self
+                .applications
+                .iter()
+                .any(|app| application.contains(app.as_str()))
+            {
+                return true;
+            }
+            persisted.iter().any(|(file, ext, _)| {
+                ext.kind() == EntityKind::Text
+                    && fs::read_to_string(file.path()).is_ok_and(|text| {
+                        self.patterns.iter().any(|pattern| pattern.is_match(&text))
+                    })
+            })
+        }
+
+        /// register the clipboard item unless it repeats the last one or is excluded
+        pub fn poll(&mut self, mio: &mut Mio) -> anyhow::Result<Vec<MioId>> {
+            use sha2::{Digest, Sha256};
+            // secrets must not even be read
+            if concealed(&offered_types()) {
+                log::info!("skipping concealed clipboard content");
+                return Ok(Vec::new());
+            }
+            let context = Metadata::context(Source::Clipboard);
+            let persisted = self.clipboard.persist()?;
+            let mut hasher = Sha256::new();
+            for (file, ext, _) in persisted.iter() {
+                hasher.update(ext.to_string());
+                hasher.update(fs::read(file.path())?);
+            }
+            let digest = hasher.finalize().to_vec();
+            if persisted.is_empty() || self.last.as_ref() == Some(&digest) {
+                return Ok(Vec::new());
+            }
+            self.last = Some(digest);
+            if self.excluded(&context, &persisted) {
+                log::info!("skipping excluded clipboard content");
+                return Ok(Vec::new());
+            }
+            mio.memorize(Source::Clipboard, &context, persisted)
         }
     }
 }
 #[cfg(feature = "clipboard")]
-pub use clipboard_impl::Clipboard;
+pub use clipboard_impl::{Clipboard, ClipboardExclusion, ClipboardWatcher};
+
+#[cfg(feature = "audio")]
+mod audio_impl {
+    use super::*;
+    use cpal::{
+        traits::{DeviceTrait, HostTrait, StreamTrait},
+        Sample,
+    };
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc::{self, RecvTimeoutError},
+            Arc,
+        },
+        thread::{self, JoinHandle},
+        time::Duration,
+    };
+
+    /// the layout of interleaved 16-bit samples
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct AudioFormat {
+        pub sample_rate: u32,
+        pub channels: u16,
+    }
+
+    /// where the samples of a voice memo come from
+    pub trait AudioInput: Send + 'static {
+        /// the name of the device
+        fn name(&self) -> String;
+        fn format(&self) -> AudioFormat;
+        /// feed interleaved samples into the sink until stopped or exhausted
+        fn record(&self, sink: &mut dyn FnMut(&[i16]), stop: &AtomicBool) -> anyhow::Result<()>;
+    }
+
+    /// an input device of the default host
+    pub struct Microphone {
+        device: cpal::Device,
+        config: cpal::SupportedStreamConfig,
+    }
+
+    impl Microphone {
+        /// the names of all input devices
+        pub fn devices() -> anyhow::Result<Vec<String>> {
+            Ok(cpal::default_host()
+                .input_devices()?
+                .filter_map(|device| device.name().ok())
+                .collect())
+        }
+
+        /// the input device of the name, or the default one
+        pub fn new(name: Option<&str>) -> anyhow::Result<Self> {
+            let host = cpal::default_host();
+            let device = match name {
+                Some(name) => host
+                    .input_devices()?
+                    .find(|device| device.name().is_ok_and(|n| n == name))
+                    .ok_or_else(|| anyhow::anyhow!("input device {} not found", name))?,
+                None => host
+                    .default_input_device()
+                    .ok_or_else(|| anyhow::anyhow!("no input device available"))?,
+            };
+            let config = device.default_input_config()?;
+            Ok(Self { device, config })
+        }
+
+        fn stream<T>(&self, tx: mpsc::Sender<Vec<i16>>) -> anyhow::Result<cpal::Stream>
+        where
+            T: cpal::SizedSample,
+            i16: cpal::FromSample<T>,
+        {
+            Ok(self.device.build_input_stream(
+                &self.config.config(),
+                move |data: &[T], _: &cpal::InputCallbackInfo| {
+                    let _ = tx.send(data.iter().map(|s| i16::from_sample(*s)).collect());
+                },
+                |e| log::error!("audio input: {}", e),
+                None,
+            )?)
+        }
+    }
+
+    impl AudioInput for Microphone {
+        fn name(&self) -> String {
+            self.device.name().unwrap_or_default()
+        }
+        fn format(&self) -> AudioFormat {
+            AudioFormat {
+                sample_rate: self.config.sample_rate().0,
+                channels: self.config.channels(),
+            }
+        }
+        fn record(&self, sink: &mut dyn FnMut(&[i16]), stop: &AtomicBool) -> anyhow::Result<()> {
+            let (tx, rx) = mpsc::channel();
+            // the stream isn't sendable on every platform, so it lives on the recording thread
+            let stream = match self.config.sample_format() {
+                cpal::SampleFormat::I16 => self.stream::<i16>(tx)?,
+                cpal::SampleFormat::U16 => self.stream::<u16>(tx)?,
+                cpal::SampleFormat::F32 => self.stream::<f32>(tx)?,
+                format => anyhow::bail!("unsupported sample format {}", format),
+            };
+            stream.play()?;
+            while !stop.load(Ordering::Relaxed) {
+                match rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok(samples) => sink(&samples),
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            drop(stream);
+            for samples in rx.try_iter() {
+                sink(&samples);
+            }
+            Ok(())
+        }
+    }
+
+    /// a wav file read as if it were an input device, e.g. for testing
+    pub struct WavInput {
+        path: PathBuf,
+        spec: hound::WavSpec,
+    }
+
+    impl WavInput {
+        pub fn new(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+            let path = path.into();
+            let spec = hound::WavReader::open(&path)?.spec();
+            Ok(Self { path, spec })
+        }
+    }
+
+    impl AudioInput for WavInput {
+        fn name(&self) -> String {
+            self.path.display().to_string()
+        }
+        fn format(&self) -> AudioFormat {
+            AudioFormat {
+                sample_rate: self.spec.sample_rate,
+                channels: self.spec.channels,
+            }
+        }
+        /// feed the whole file as fast as it's read, unless stopped midway
+        fn record(&self, sink: &mut dyn FnMut(&[i16]), stop: &AtomicBool) -> anyhow::Result<()> {
+            let mut reader = hound::WavReader::open(&self.path)?;
+            let bits = self.spec.bits_per_sample as u32;
+            let samples: Vec<i16> = match self.spec.sample_format {
+                hound::SampleFormat::Float => reader
+                    .samples::<f32>()
+                    .map(|s| Ok(i16::from_sample(s?)))
+                    .collect::<anyhow::Result<_>>()?,
+                hound::SampleFormat::Int => reader
+                    .samples::<i32>()
+                    .map(|s| {
+                        let s = s?;
+                        Ok(if bits >= 16 {
+                            s >> (bits - 16)
+                        } else {
+                            s << (16 - bits)
+                        } as i16)
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            };
+            // a tenth of a second at a time, like a device would
+            let chunk = (self.spec.sample_rate as usize * self.spec.channels as usize / 10).max(1);
+            for samples in samples.chunks(chunk) {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                sink(samples);
+            }
+            Ok(())
+        }
+    }
+
+    /// a voice memo being recorded in the background
+    pub struct VoiceRecorder {
+        device: String,
+        format: AudioFormat,
+        started: SystemTime,
+        stop: Arc<AtomicBool>,
+        handle: JoinHandle<anyhow::Result<Vec<i16>>>,
+    }
+
+    impl VoiceRecorder {
+        pub fn start(input: impl AudioInput) -> Self {
+            let stop = Arc::new(AtomicBool::new(false));
+            let (device, format) = (input.name(), input.format());
+            let handle = {
+                let stop = stop.clone();
+                thread::spawn(move || {
+                    let mut samples = Vec::new();
+                    input.record(&mut |chunk| samples.extend_from_slice(chunk), &stop)?;
+                    Ok(samples)
+                })
+            };
+            Self {
+                device,
+                format,
+                started: SystemTime::now(),
+                stop,
+                handle,
+            }
+        }
+
+        /// whether the input is exhausted or broken, so that only stopping is left
+        pub fn is_finished(&self) -> bool {
+            self.handle.is_finished()
+        }
+
+        pub fn stop(self) -> anyhow::Result<VoiceMemo> {
+            self.stop.store(true, Ordering::Relaxed);
+            let samples = self
+                .handle
+                .join()
+                .map_err(|_| anyhow::anyhow!("recording panicked"))??;
+            Ok(VoiceMemo {
+                device: self.device,
+                format: self.format,
+                started: self.started,
+                samples,
+            })
+        }
+    }
+
+    /// the sample rate opus is always encoded at
+    const OPUS_RATE: u32 = 48_000;
+    /// the samples per channel in each packet, i.e. 20 ms
+    const OPUS_FRAME: usize = 960;
+
+    /// a recorded voice memo, encoded to ogg opus upon registration
+    pub struct VoiceMemo {
+        pub device: String,
+        pub format: AudioFormat,
+        pub started: SystemTime,
+        /// interleaved samples
+        pub samples: Vec<i16>,
+    }
+
+    impl VoiceMemo {
+        pub fn duration(&self) -> Duration {
+            let frames = self.samples.len() / self.format.channels.max(1) as usize;
+            Duration::from_secs_f64(frames as f64 / self.format.sample_rate.max(1) as f64)
+        }
+
+        /// the samples resampled to the opus rate, downmixed to mono beyond stereo
+        fn resampled(&self) -> (usize, Vec<i16>) {
+            let src = self.format.channels.max(1) as usize;
+            let channels = if src == 2 { 2 } else { 1 };
+            let frames = self.samples.len() / src;
+            let sample = |frame: usize, channel: usize| -> f64 {
+                let frame = &self.samples[frame * src..(frame + 1) * src];
+                if channels == src {
+                    frame[channel] as f64
+                } else {
+                    frame.iter().map(|s| *s as f64).sum::<f64>() / src as f64
+                }
+            };
+            let ratio = self.format.sample_rate.max(1) as f64 / OPUS_RATE as f64;
+            let len = (frames as f64 / ratio) as usize;
+            let mut pcm = Vec::with_capacity(len * channels);
+            for n in 0..len {
+                let at = n as f64 * ratio;
+                let (i, t) = (at as usize, at.fract());
+                let j = (i + 1).min(frames - 1);
+                for channel in 0..channels {
+                    let s = sample(i, channel) * (1.0 - t) + sample(j, channel) * t;
+                    pcm.push(s.round() as i16);
+                }
+            }
+            (channels, pcm)
+        }
+
+        fn encode(&self) -> anyhow::Result<NamedTempFile> {
+            use audiopus::{coder::Encoder, Application, Channels, SampleRate};
+            use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+            let (channels, pcm) = self.resampled();
+            let encoder = Encoder::new(
+                SampleRate::Hz48000,
+                if channels == 2 {
+                    Channels::Stereo
+                } else {
+                    Channels::Mono
+                },
+                Application::Voip,
+            )?;
+            let pre_skip = encoder.lookahead()? as u16;
+            let file = tempfile::Builder::new()
+                .suffix(&format!(".{}", EntityExt::Ogg))
+                .tempfile()?;
+            let mut writer = PacketWriter::new(std::io::BufWriter::new(file.reopen()?));
+            let serial = self
+                .started
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |d| d.subsec_nanos());
+
+            // the identification and comment headers, each on a page of its own
+            let mut head = b"OpusHead".to_vec();
+            head.push(1);
+            head.push(channels as u8);
+            head.extend(pre_skip.to_le_bytes());
+            head.extend(self.format.sample_rate.to_le_bytes());
+            head.extend(0i16.to_le_bytes());
+            head.push(0);
+            writer.write_packet(head.into(), serial, PacketWriteEndInfo::EndPage, 0)?;
+            let vendor = concat!("mio ", env!("CARGO_PKG_VERSION"));
+            let mut tags = b"OpusTags".to_vec();
+            tags.extend((vendor.len() as u32).to_le_bytes());
+            tags.extend(vendor.as_bytes());
+            tags.extend(0u32.to_le_bytes());
+            writer.write_packet(tags.into(), serial, PacketWriteEndInfo::EndPage, 0)?;
+
+            let frames = pcm.len() / channels;
+            let packets = frames.div_ceil(OPUS_FRAME).max(1);
+            let mut buf = vec![0; 4000];
+            for (n, chunk) in pcm
+                .chunks(OPUS_FRAME * channels)
+                .chain(pcm.is_empty().then_some(&[][..]))
+                .enumerate()
+            {
+                // the last packet is padded with silence, which the granule position trims
+                let mut frame = chunk.to_vec();
+                frame.resize(OPUS_FRAME * channels, 0);
+                let len = encoder.encode(&frame, &mut buf)?;
+                let last = n + 1 == packets;
+                let granule = pre_skip as u64 + ((n + 1) * OPUS_FRAME).min(frames) as u64;
+                let end = if last {
+                    PacketWriteEndInfo::EndStream
+                } else {
+                    PacketWriteEndInfo::NormalPacket
+                };
+                writer.write_packet(buf[..len].into(), serial, end, granule)?;
+            }
+            writer.into_inner().flush()?;
+            Ok(file)
+        }
+    }
+
+    impl Persistable for VoiceMemo {
+        fn source(&self) -> Source {
+            Source::VoiceMemo
+        }
+        fn persist(&self) -> anyhow::Result<Vec<(NamedTempFile, EntityExt, Metadata)>> {
+            if self.samples.is_empty() {
+                anyhow::bail!("nothing is recorded")
+            }
+            let metadata = Metadata {
+                content: Some(EntityKind::Audio),
+                mime: Some(EntityExt::Ogg.mime().to_string()),
+                device: Some(self.device.clone()),
+                modified: Some(self.started),
+                ..Default::default()
+            };
+            Ok(vec![(self.encode()?, EntityExt::Ogg, metadata)])
+        }
+    }
+
+    impl Interpretable for VoiceMemo {
+        type Mio<'a> = &'a mut Mio;
+        type Target<'a> = Vec<MioId>;
+        fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
+            mio.register(&self)
+        }
+    }
+}
+#[cfg(feature = "audio")]
+pub use audio_impl::*;
+
+#[cfg(all(feature = "screenshot", feature = "video"))]
+mod recording_impl {
+    use super::*;
+    use screenshots::Screen;
+    use std::{
+        process::Stdio,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::{self, JoinHandle},
+        time::{Duration, Instant},
+    };
+
+    /// where the frames of a screen recording come from
+    pub trait FrameSource: Send + 'static {
+        /// the next frame, scaled to the size of the first one if it differs
+        fn frame(&mut self) -> anyhow::Result<image::RgbaImage>;
+        /// the display captured, if any
+        fn display(&self) -> Option<Monitor> {
+            None
+        }
+    }
+
+    /// synthetic frames, e.g. for testing
+    impl<F> FrameSource for F
+    where
+        F: FnMut() -> anyhow::Result<image::RgbaImage> + Send + 'static,
+    {
+        fn frame(&mut self) -> anyhow::Result<image::RgbaImage> {
+            self()
+        }
+    }
+
+    /// the frames of a display
+    pub struct ScreenFrames(Screen);
+
+    impl ScreenFrames {
+        /// the display of the id, or the primary one
+        pub fn new(display: Option<u32>) -> anyhow::Result<Self> {
+            let screens = Screen::all()?;
+            let screen = match display {
+                Some(id) => screens
+                    .into_iter()
+                    .find(|screen| screen.display_info.id == id)
+                    .ok_or_else(|| anyhow::anyhow!("display {} not found", id))?,
+                None => screens
+                    .into_iter()
+                    .find(|screen| screen.display_info.is_primary)
+                    .ok_or_else(|| anyhow::anyhow!("no display available"))?,
+            };
+            Ok(Self(screen))
+        }
+    }
+
+    impl FrameSource for ScreenFrames {
+        fn frame(&mut self) -> anyhow::Result<image::RgbaImage> {
+            self.0.capture()
+        }
+        fn display(&self) -> Option<Monitor> {
+            Some(super::screenshot_impl::monitor(&self.0))
+        }
+    }
+
+    /// how a screen is recorded
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct RecordingOptions {
+        /// the display to be recorded, or the primary one
+        #[serde(default)]
+        pub display: Option<u32>,
+        /// frames per second
+        pub fps: u32,
+        /// the recording stops by itself after so many seconds
+        pub limit: u64,
+    }
+
+    impl Default for RecordingOptions {
+        fn default() -> Self {
+            Self {
+                display: None,
+                fps: 5,
+                limit: 30 * 60,
+            }
+        }
+    }
+
+    /// a screen recording in the background, encoded to mp4 as it goes by an `ffmpeg` in `PATH`
+    /// that was built with libx264
+    pub struct ScreenRecorder {
+        display: Option<Monitor>,
+        started: SystemTime,
+        stop: Arc<AtomicBool>,
+        handle: JoinHandle<anyhow::Result<NamedTempFile>>,
+    }
+
+    impl ScreenRecorder {
+        pub fn start(source: impl FrameSource, options: &RecordingOptions) -> Self {
+            let stop = Arc::new(AtomicBool::new(false));
+            let display = source.display();
+            let handle = {
+                let stop = stop.clone();
+                let fps = options.fps.max(1);
+                let limit = Duration::from_secs(options.limit);
+                thread::spawn(move || Self::record(source, fps, limit, &stop))
+            };
+            Self {
+                display,
+                started: SystemTime::now(),
+                stop,
+                handle,
+            }
+        }
+
+        fn record(
+            mut source: impl FrameSource,
+            fps: u32,
+            limit: Duration,
+            stop: &AtomicBool,
+        ) -> anyhow::Result<NamedTempFile> {
+            let frame = source.frame()?;
+            let (width, height) = frame.dimensions();
+            let video = tempfile::Builder::new()
+                .suffix(&format!(".{}", EntityExt::Mp4))
+                .tempfile()?;
+            let mut ffmpeg = Command::new("ffmpeg")
+                .args(["-v", "error", "-y"])
+                .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+                .args(["-s", &format!("{}x{}", width, height)])
+                .args(["-r", &fps.to_string()])
+                .args(["-i", "pipe:0"])
+                // h.264 wants even dimensions
+                .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+                .args(["-c:v", "libx264", "-pix_fmt", "yuv420p"])
+                .arg(video.path())
+                .stdin(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            let mut stdin = ffmpeg
+                .stdin
+                .take()
+                .ok_or_else(|| anyhow::anyhow!("can't feed ffmpeg"))?;
+
+            let fed = Self::feed(&mut source, frame, fps, limit, stop, &mut stdin);
+            drop(stdin);
+            if let Err(e) = fed {
+                let _ = ffmpeg.kill();
+                return Err(e);
+            }
+            let output = ffmpeg.wait_with_output()?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "ffmpeg failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )
+            }
+            Ok(video)
+        }
+
+        /// write raw frames to the sink at the pace of the clock until stopped or out of time,
+        /// giving the number of frames written
+        pub(super) fn feed(
+            source: &mut impl FrameSource,
+            mut frame: image::RgbaImage,
+            fps: u32,
+            limit: Duration,
+            stop: &AtomicBool,
+            sink: &mut impl Write,
+        ) -> anyhow::Result<u32> {
+            let (width, height) = frame.dimensions();
+            let interval = Duration::from_secs_f64(1.0 / fps as f64);
+            let started = Instant::now();
+            let mut frames = 0u32;
+            loop {
+                // a late frame also fills the slots it missed, to keep up with the clock
+                let due = ((started.elapsed().as_secs_f64() * fps as f64) as u32).max(frames + 1);
+                while frames < due {
+                    sink.write_all(frame.as_raw())?;
+                    frames += 1;
+                }
+                if stop.load(Ordering::Relaxed) || started.elapsed() >= limit {
+                    return Ok(frames);
+                }
+                thread::sleep(
+                    (started + interval * frames).saturating_duration_since(Instant::now()),
+                );
+                frame = source.frame()?;
+                if frame.dimensions() != (width, height) {
+                    frame = image::imageops::resize(
+                        &frame,
+                        width,
+                        height,
+                        image::imageops::FilterType::Triangle,
+                    );
+                }
+            }
+        }
+
+        /// whether the limit is reached or the capture broke, so that only stopping is left
+        pub fn is_finished(&self) -> bool {
+            self.handle.is_finished()
+        }
+
+        pub fn stop(self) -> anyhow::Result<ScreenRecording> {
+            self.stop.store(true, Ordering::Relaxed);
+            let video = self
+                .handle
+                .join()
+                .map_err(|_| anyhow::anyhow!("recording panicked"))??;
+            Ok(ScreenRecording {
+                video,
+                display: self.display,
+                started: self.started,
+            })
+        }
+    }
+
+    /// a finished screen recording
+    pub struct ScreenRecording {
+        video: NamedTempFile,
+        pub display: Option<Monitor>,
+        pub started: SystemTime,
+    }
+
+    impl Persistable for ScreenRecording {
+        fn source(&self) -> Source {
+            Source::ScreenRecording
+        }
+        fn persist(&self) -> anyhow::Result<Vec<(NamedTempFile, EntityExt, Metadata)>> {
+            let file = NamedTempFile::new()?;
+            fs::copy(self.video.path(), file.path())?;
+            let metadata = Metadata {
+                content: Some(EntityKind::Video),
+                mime: Some(EntityExt::Mp4.mime().to_string()),
+                display: self.display,
+                modified: Some(self.started),
+                ..Default::default()
+            };
+            Ok(vec![(file, EntityExt::Mp4, metadata)])
+        }
+    }
+
+    impl Interpretable for ScreenRecording {
+        type Mio<'a> = &'a mut Mio;
+        type Target<'a> = Vec<MioId>;
+        fn interpret<'a>(self, mio: Self::Mio<'a>) -> anyhow::Result<Self::Target<'a>> {
+            mio.register(&self)
+        }
+    }
+}
+#[cfg(all(feature = "screenshot", feature = "video"))]
+pub use recording_impl::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn output_within_gives_up_in_time() {
+        let args = ["-c", "echo mio"];
+        let out = output_within("sh", &args, Duration::from_secs(5)).unwrap();
+        assert_eq!(out.as_deref(), Some("mio"));
+        let start = Instant::now();
+        assert!(output_within("sleep", &["5"], Duration::from_millis(100)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(output_within("mio-no-such-program", &[], Duration::from_secs(1)).is_err());
+    }
+
+    #[cfg(feature = "audio")]
+    #[test]
+    fn voice_memo_from_wav() {
+        let (tmp, dirs) = MioDirs::temp();
+        let wav = tmp.path().join("memo.wav");
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&wav, spec).unwrap();
+        for n in 0..24_000 {
+            let s = (n as f32 * 0.05).sin() * 8000.0;
+            writer.write_sample(s as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let recorder = VoiceRecorder::start(WavInput::new(&wav).unwrap());
+        while !recorder.is_finished() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let memo = recorder.stop().unwrap();
+        assert_eq!(memo.duration(), Duration::from_millis(1500));
+
+        let mut mio = Mio::with_dirs(dirs);
+        let ids = memo.interpret(&mut mio).unwrap();
+        let [id] = ids[..] else {
+            panic!("expected one entity, got {:?}", ids)
+        };
+        let entity = &mio.ring.entities[&id];
+        assert_eq!(entity.ext, EntityExt::Ogg);
+        assert_eq!(entity.body.metadata.source, Some(Source::VoiceMemo));
+        assert_eq!(entity.body.metadata.device, Some(wav.display().to_string()));
+        // the granule position of the last page counts the samples at 48 kHz past the pre-skip
+        let bytes = entity.read(&mio.dirs).unwrap();
+        let mut reader = ogg::PacketReader::new(std::io::Cursor::new(bytes));
+        let head = reader.read_packet().unwrap().unwrap();
+        assert!(head.data.starts_with(b"OpusHead"));
+        let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as u64;
+        let mut granule = 0;
+        while let Some(packet) = reader.read_packet().unwrap() {
+            granule = packet.absgp_page();
+        }
+        assert_eq!(granule - pre_skip, 72_000);
+    }
+
+    #[cfg(all(feature = "screenshot", feature = "video"))]
+    #[test]
+    fn synthetic_frames_keep_their_size() {
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        let red = image::RgbaImage::from_pixel(4, 2, image::Rgba([255, 0, 0, 255]));
+        let mut source = {
+            let stop = stop.clone();
+            let mut taken = 0;
+            move || {
+                taken += 1;
+                if taken == 3 {
+                    stop.store(true, Ordering::Relaxed);
+                }
+                // later frames come in larger, as if the display was rescaled
+                Ok(image::RgbaImage::from_pixel(
+                    8,
+                    4,
+                    image::Rgba([0, 0, 255, 255]),
+                ))
+            }
+        };
+        let mut sink = Vec::new();
+        let limit = Duration::from_secs(60);
+        let frames = ScreenRecorder::feed(&mut source, red, 50, limit, &stop, &mut sink).unwrap();
+        assert!(frames >= 4);
+        assert_eq!(sink.len(), frames as usize * 4 * 2 * 4);
+        assert_eq!(sink[..4], [255, 0, 0, 255]);
+        assert_eq!(sink[sink.len() - 4..], [0, 0, 255, 255]);
+    }
+
+    #[cfg(feature = "screenshot")]
+    #[test]
+    fn unchanged_screens_are_told_apart() {
+        // a horizontal gradient, dimmed or with a stray pixel it hashes the same
+        let gradient = |dim: u8| {
+            image::RgbImage::from_fn(90, 80, |x, _| {
+                let v = (x * 255 / 90) as u8 / dim;
+                image::Rgb([v, v, v])
+            })
+        };
+        let shot = gradient(1);
+        let mut noisy = shot.clone();
+        noisy.put_pixel(3, 3, image::Rgb([255, 255, 255]));
+        let flipped = image::imageops::flip_horizontal(&shot);
+        let hash = |image: image::RgbImage| screenshot_impl::dhash(&image.into());
+        assert_eq!(hash(shot.clone()), u64::MAX);
+        assert_eq!(hash(gradient(2)), hash(shot.clone()));
+        assert!((hash(noisy) ^ hash(shot.clone())).count_ones() <= 4);
+        assert_eq!(hash(flipped), 0);
+
+        let mut capturer = PeriodicCapturer::new(PeriodicCapture::default());
+        assert!(!capturer.unchanged(&[hash(shot.clone())]));
+        capturer.hashes = vec![hash(shot.clone())];
+        assert!(capturer.unchanged(&[hash(shot.clone()) ^ 0b1011]));
+        assert!(!capturer.unchanged(&[hash(shot.clone()) ^ 0b11111]));
+        // a display came or went
+        assert!(!capturer.unchanged(&[hash(shot.clone()); 2]));
+    }
+
+    #[test]
+    fn automatic_captures_can_be_left_out_of_views() {
+        let (_tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let mut persisted = |automatic: bool| {
+            let mut file = NamedTempFile::new().unwrap();
+            file.write_all(b"mio").unwrap();
+            let metadata = Metadata {
+                automatic,
+                ..Default::default()
+            };
+            let context = Metadata::default();
+            let ids = mio
+                .memorize(
+                    Source::ScreenShot,
+                    &context,
+                    vec![(file, EntityExt::Txt, metadata)],
+                )
+                .unwrap();
+            ids[0]
+        };
+        let manual = [persisted(false), persisted(false)];
+        let automatic = persisted(true);
+        let all = MioView::all(&mio);
+        assert_eq!(all.timeline.len(), 3);
+        let view = MioViewGen::Manual(Box::new(MioViewGen::All))
+            .interpret(&mio)
+            .unwrap();
+        assert_eq!(view.timeline.iter().map(|e| e.base).collect_vec(), manual);
+        assert!(!view.ring.entities.contains_key(&automatic));
+        // pages are filled with what is kept
+        let page = MioViewGen::Manual(Box::new(MioViewGen::Page {
+            cursor: None,
+            limit: 1,
+            direction: Direction::Older,
+        }))
+        .interpret(&mio)
+        .unwrap();
+        assert_eq!(
+            page.timeline.iter().map(|e| e.base).collect_vec(),
+            [manual[1]]
+        );
+        assert!(page.next.is_some());
+    }
+
+    #[test]
+    fn entities_are_indexed_only_once_written() {
+        let (_tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let file = NamedTempFile::new().unwrap();
+        fs::remove_file(file.path()).unwrap();
+        let persisted = vec![(file, EntityExt::Txt, Metadata::default())];
+        let context = Metadata::default();
+        assert!(mio.memorize(Source::Import, &context, persisted).is_err());
+        assert!(mio.ring.entities.is_empty());
+        assert_eq!(mio.chronology.iter().count(), 0);
+    }
+
+    #[test]
+    fn only_focused_sources_take_the_focus() {
+        let context = Metadata {
+            application: Some("editor".into()),
+            window: Some("notes".into()),
+            hostname: Some("host".into()),
+            ..Default::default()
+        };
+        let mut shot = Metadata::default();
+        shot.complete(Source::ScreenShot, &context);
+        assert_eq!(shot.application.as_deref(), Some("editor"));
+        assert_eq!(shot.window.as_deref(), Some("notes"));
+        for source in [Source::Import, Source::VoiceMemo, Source::ScreenRecording] {
+            let mut metadata = Metadata::default();
+            metadata.complete(source, &context);
+            assert_eq!(metadata.source, Some(source));
+            assert_eq!((metadata.application, metadata.window), (None, None));
+            assert_eq!(metadata.hostname.as_deref(), Some("host"));
+        }
+    }
+
+    #[test]
+    fn import_skips_what_it_cannot_take() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "mio").unwrap();
+        fs::write(dir.path().join("b.bin"), [0u8, 1, 2, 3]).unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub").join("c.json"), "[1]").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("sub").join("loop")).unwrap();
+        let import = FileImport {
+            recursive: true,
+            ..FileImport::new([dir.path()])
+        };
+        let files = import.files().unwrap();
+        assert_eq!(files.len(), 3);
+        let persisted = import.persist().unwrap();
+        let exts = persisted.iter().map(|(_, ext, _)| *ext).collect_vec();
+        assert_eq!(exts, vec![EntityExt::Txt, EntityExt::Json]);
+        assert_eq!(fs::read(persisted[0].0.path()).unwrap(), b"mio");
+        // registered one by one, the same are skipped
+        let (_tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let ids = import.interpret(&mut mio).unwrap();
+        let exts = (ids.iter())
+            .map(|id| mio.specterish(id).unwrap().extension())
+            .collect_vec();
+        assert_eq!(exts, vec![EntityExt::Txt, EntityExt::Json]);
+        assert_eq!(
+            mio.specterish(&ids[0]).unwrap().read(&mio.dirs).unwrap(),
+            b"mio"
+        );
+        assert_eq!(mio.chronology.iter().count(), 2);
+    }
+
+    #[test]
+    fn sniff_reads_the_head_first() {
+        let mut file = NamedTempFile::new().unwrap();
+        // a multibyte character straddles the end of the head
+        let mut text = "m".repeat(SNIFF_LEN as usize - 1);
+        text.push_str("ミオ");
+        file.write_all(text.as_bytes()).unwrap();
+        assert_eq!(sniff_file(file.path()).unwrap(), Some(EntityExt::Txt));
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"\x89PNG\r\n\x1a\n").unwrap();
+        file.write_all(&vec![0; SNIFF_LEN as usize]).unwrap();
+        assert_eq!(sniff_file(file.path()).unwrap(), Some(EntityExt::Png));
+    }
+
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn urls_are_told_from_text() {
+        use clipboard_impl::is_url;
+        assert!(is_url("https://example.com/a?b=c"));
+        assert!(is_url("  ftp://example.com\n"));
+        assert!(is_url("git+ssh://host/repo"));
+        assert!(!is_url("example.com"));
+        assert!(!is_url("https://"));
+        assert!(!is_url("://example.com"));
+        assert!(!is_url("1http://example.com"));
+        assert!(!is_url("see https://example.com"));
+        assert!(!is_url("https://example.com and more"));
+    }
+
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn concealed_clipboard_types() {
+        use clipboard_impl::concealed;
+        let types = |types: &[&str]| types.iter().map(|t| t.to_string()).collect_vec();
+        assert!(!concealed(&types(&["UTF8_STRING", "text/plain"])));
+        assert!(concealed(&types(&[
+            "text/plain",
+            "x-kde-passwordManagerHint"
+        ])));
+        assert!(concealed(&types(&[
+            "public.utf8-plain-text",
+            "org.nspasteboard.ConcealedType"
+        ])));
+        assert!(concealed(&types(&["org.nspasteboard.TransientType"])));
+    }
+
+    #[cfg(feature = "screenshot")]
+    #[test]
+    fn window_names_are_escaped() {
+        use screenshot_impl::escape_ere;
+        assert_eq!(escape_ere("mio - ring"), "mio - ring");
+        assert_eq!(escape_ere("a.b (1) [x]"), r"a\.b \(1\) \[x\]");
+        assert_eq!(escape_ere(r"$5 * 2^3 | c:\"), r"\$5 \* 2\^3 \| c:\\");
+    }
+}
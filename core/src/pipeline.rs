@@ -0,0 +1,266 @@
+use super::*;
+use std::sync::PoisonError;
+
+/// a rule that initiates an operation upon each newly registered entity it matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    /// only entities from the source, or from any source if absent
+    #[serde(default)]
+    pub source: Option<Source>,
+    /// only entities of the extension, or of any extension if absent
+    #[serde(default)]
+    pub ext: Option<EntityExt>,
+    /// the operation to be initiated
+    pub kind: OperationKind,
+    #[serde(default)]
+    pub attr: serde_json::Value,
+    /// whether to actualize the resulting specters in the background right away
+    #[serde(default)]
+    pub force: bool,
+}
+
+impl Rule {
+    pub fn new(attr: impl Operable) -> Self {
+        Self {
+            source: None,
+            ext: None,
+            kind: attr.kind(),
+            attr: serde_json::to_value(attr).expect("failed to serialize attribute"),
+            force: false,
+        }
+    }
+    pub fn from_source(mut self, source: Source) -> Self {
+        self.source = Some(source);
+        self
+    }
+    pub fn on_ext(mut self, ext: EntityExt) -> Self {
+        self.ext = Some(ext);
+        self
+    }
+    pub fn forced(mut self) -> Self {
+        self.force = true;
+        self
+    }
+
+    pub fn matches(&self, source: Source, ext: EntityExt) -> bool {
+        self.source.is_none_or(|s| s == source) && self.ext.is_none_or(|e| e == ext)
+    }
+}
+
+/// the user-defined rules, stored as `pipelines.json` in the config dir
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Pipelines {
+    pub rules: Vec<Rule>,
+}
+
+impl Pipelines {
+    pub fn path(dirs: &MioDirs) -> PathBuf {
+        dirs.config_dir.join("pipelines.json")
+    }
+
+    pub fn read(dirs: &MioDirs) -> anyhow::Result<Self> {
+        let path = Self::path(dirs);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// read the pipelines, falling back to none if the config is broken
+    pub fn read_or_default(dirs: &MioDirs) -> Self {
+        Self::read(dirs).unwrap_or_else(|e| {
+            log::warn!("can't parse pipelines, ignoring them: {:#}", e);
+            Self::default()
+        })
+    }
+
+    pub fn write(&self, dirs: &MioDirs) -> anyhow::Result<()> {
+        fs::write(Self::path(dirs), serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl Mio {
+    /// initiate the operations of all matching rules upon newly registered entities
+    pub(crate) fn trigger(&mut self, source: Source, ids: &[MioId]) {
+        let rules = self.pipelines.rules.clone();
+        let mut forced = HashSet::new();
+        for id in ids {
+            let ext = match self.specterish(id) {
+                Ok(specter) => specter.extension(),
+                Err(e) => {
+                    log::warn!("failed to trigger rules: {:#}", e);
+                    continue;
+                }
+            };
+            for rule in rules.iter().filter(|rule| rule.matches(source, ext)) {
+                let initiate = MioInitiate {
+                    kind: rule.kind,
+                    attr: rule.attr.clone(),
+                    base: vec![*id],
+                };
+                match initiate.interpret(self) {
+                    // the incremental ring only holds the base as an entity
+                    Ok(ring) if rule.force => forced.extend(ring.specters.into_keys()),
+                    Ok(_) => {}
+                    // a broken rule must not fail the registration
+                    Err(e) => log::warn!("pipeline failed upon {}: {:#}", id.stem(), e),
+                }
+            }
+        }
+        if !forced.is_empty() {
+            self.background(forced);
+        }
+    }
+
+    /// actualize the specters in the background upon a snapshot of what they need
+    pub fn background(&mut self, ids: HashSet<MioId>) {
+        let snapshot = self.snapshot(&ids);
+        let job = self.executor.spawn(Arc::new(snapshot), ids);
+        self.jobs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(job);
+    }
+
+    /// a mio holding only what actualizing the specters takes: the specters, the operations
+    /// resulting in them along with their siblings, and the bases of those, transitively
+    fn snapshot(&self, ids: &HashSet<MioId>) -> Mio {
+        let mut ring = MioRing::new();
+        let mut todo = ids.iter().copied().collect_vec();
+        while let Some(id) = todo.pop() {
+            if let Some(entity) = self.ring.entities.get(&id) {
+                ring.entities.insert(id, entity.clone());
+                continue;
+            }
+            let Some(specter) = self.ring.specters.get(&id) else {
+                continue;
+            };
+            if ring.specters.insert(id, specter.clone()).is_some() {
+                continue;
+            }
+            if let Some(operation) = self.ring.operations.get(&specter.body.operation) {
+                ring.operations.insert(operation.id, operation.clone());
+                todo.extend(operation.base.iter().copied());
+                todo.extend(operation.specters());
+            }
+        }
+        Mio {
+            dirs: self.dirs.clone(),
+            registry: self.registry.clone(),
+            pipelines: Pipelines::default(),
+            executor: self.executor.clone(),
+            digests: self.digests.clone(),
+            jobs: Default::default(),
+            alloc: Alloc::default(),
+            null: self.null,
+            chronology: Chronology::new(),
+            ring,
+            archived: MioRing::new(),
+            ingested: HashMap::new(),
+        }
+    }
+
+    /// record the results of the finished background jobs, or wait for all of them
+    pub fn settle(&mut self, wait: bool) -> MioForced {
+        let done: Vec<_> = {
+            let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+            let (done, running) = std::mem::take(&mut *jobs)
+                .into_iter()
+                .partition(|job| wait || job.is_finished());
+            *jobs = running;
+            done
+        };
+        let mut forced = MioForced::new();
+        for job in done {
+            match job.join() {
+                Ok(res) => forced.extend(res),
+                Err(e) => log::error!("{:#}", e),
+            }
+        }
+        for (id, res) in forced.iter() {
+            self.record(id, res);
+        }
+        forced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_matches() {
+        let rule = Rule {
+            source: None,
+            ext: None,
+            kind: OperationKind::Resize,
+            attr: serde_json::Value::Null,
+            force: false,
+        };
+        assert!(rule.matches(Source::Clipboard, EntityExt::Png));
+        let rule = rule.from_source(Source::ScreenShot);
+        assert!(rule.matches(Source::ScreenShot, EntityExt::Png));
+        assert!(!rule.matches(Source::Clipboard, EntityExt::Png));
+        let rule = rule.on_ext(EntityExt::Png);
+        assert!(rule.matches(Source::ScreenShot, EntityExt::Png));
+        assert!(!rule.matches(Source::ScreenShot, EntityExt::Txt));
+    }
+
+    #[test]
+    fn rules_default_when_parsed() {
+        let pipelines: Pipelines =
+            serde_json::from_str(r#"{ "rules": [{ "source": "Clipboard", "kind": "Resize" }] }"#)
+                .unwrap();
+        let rule = &pipelines.rules[0];
+        assert!(!rule.force);
+        assert!(rule.matches(Source::Clipboard, EntityExt::Txt));
+        assert!(!rule.matches(Source::Import, EntityExt::Txt));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn snapshots_hold_only_what_is_needed() {
+        let (tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let paths = ["a.png", "b.png"].map(|name| tmp.path().join(name));
+        for path in paths.iter() {
+            image::RgbImage::new(2, 2).save(path).unwrap();
+        }
+        let entities = FileImport::new(paths).interpret(&mut mio).unwrap();
+        let convert = |mio: &mut Mio, base| {
+            let attr = ConvertImage {
+                ext: EntityExt::Jpg,
+                quality: None,
+            };
+            let ring = MioInitiate::new(attr, vec![base]).interpret(mio).unwrap();
+            let operation = ring.operations.into_values().exactly_one().unwrap();
+            operation.output(Role::Primary).unwrap()
+        };
+        let converted = convert(&mut mio, entities[0]);
+        let twice = convert(&mut mio, converted);
+        let other = convert(&mut mio, entities[1]);
+
+        let snapshot = mio.snapshot(&HashSet::from([twice]));
+        assert_eq!(snapshot.ring.entities.keys().collect_vec(), [&entities[0]]);
+        let specters = snapshot
+            .ring
+            .specters
+            .keys()
+            .copied()
+            .collect::<HashSet<_>>();
+        assert_eq!(specters, HashSet::from([converted, twice]));
+        assert_eq!(snapshot.ring.operations.len(), 2);
+        assert!(!snapshot.ring.contains(&other));
+        assert!(snapshot.chronology.is_empty());
+
+        let forced = MioExecutor::new(2).execute(
+            &snapshot,
+            HashSet::from([twice]),
+            &std::sync::mpsc::channel().0,
+            &Default::default(),
+        );
+        assert!(forced[&twice].is_ok());
+        assert!(mio.specterish(&twice).unwrap().exists(&mio.dirs));
+    }
+}
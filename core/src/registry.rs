@@ -0,0 +1,215 @@
+use super::*;
+use std::{marker::PhantomData, sync::Arc};
+
+/// the provider of an operation, which knows what it accepts and how to run it
+pub trait Provider: Send + Sync {
+    /// the display name of the operation
+    fn name(&self) -> &str;
+    /// the kind of the operation
+    fn kind(&self) -> OperationKind;
+    /// whether the operation can be done upon base specters of the kinds
+    fn accepts(&self, src: &[EntityKind]) -> bool;
+    /// the extension the primary resulting specter has unless the attributes choose another,
+    /// which tells the kind the operation yields
+    fn ext(&self) -> EntityExt;
+    /// the version of the implementation; previous results are recomputed once it changes
+    fn version(&self) -> String {
+        String::new()
+    }
+    /// validate the attributes against the base specters,
+    /// and lay out the roles and extensions of the resulting specters
+    fn prepare(
+        &self,
+        attr: &serde_json::Value,
+        base: &[MioId],
+        mio: &Mio,
+    ) -> Result<Vec<(Role, EntityExt)>, OperationError>;
+    /// actualize all resulting specters of the operation
+    fn run(&self, op: &Operation, mio: &Mio) -> anyhow::Result<()>;
+}
+
+/// an operable upon a single base specter, with the metadata to be registered
+pub trait Registrable: Operable + 'static {
+    /// the display name of the operation
+    const NAME: &'static str;
+    /// the kinds of the base specter that the operation accepts
+    const ACCEPTS: &'static [EntityKind];
+    /// the extension the primary resulting specter has unless the attributes choose another
+    const EXT: EntityExt;
+    /// to be bumped whenever the results would differ, e.g. after upgrading the engine
+    const VERSION: u32 = 0;
+    /// the kind of the operation
+    fn operation() -> OperationKind;
+}
+
+/// the provider of a registrable operable
+struct Registered<T>(PhantomData<fn() -> T>);
+
+impl<T: Registrable> Provider for Registered<T> {
+    fn name(&self) -> &str {
+        T::NAME
+    }
+    fn kind(&self) -> OperationKind {
+        T::operation()
+    }
+    fn accepts(&self, src: &[EntityKind]) -> bool {
+        matches!(src, [kind] if T::ACCEPTS.contains(kind))
+    }
+    fn ext(&self) -> EntityExt {
+        T::EXT
+    }
+    fn version(&self) -> String {
+        format!("{}@{}", T::NAME, T::VERSION)
+    }
+    fn prepare(
+        &self,
+        attr: &serde_json::Value,
+        base: &[MioId],
+        mio: &Mio,
+    ) -> Result<Vec<(Role, EntityExt)>, OperationError> {
+        Ok(T::prepare(attr, base, mio)?.outputs())
+    }
+    fn run(&self, op: &Operation, mio: &Mio) -> anyhow::Result<()> {
+        let base = op.base.clone().into_iter().exactly_one()?;
+        // ensure that the base is actualized
+        let base = mio.specterish(&base)?;
+        base.run(mio)?;
+        // validated upon initiation already; `execute` checks whatever depends on the content,
+        // so that the base is neither decrypted nor probed twice
+        let operable: T = serde_json::from_value(op.attr.clone())?;
+        let res = operable.execute(base.read_as_temp(&mio.dirs)?)?;
+        op.write(mio, res)
+    }
+}
+
+/// the registry of all operations that can be done upon specters
+#[derive(Clone)]
+pub struct OperationRegistry {
+    providers: Vec<Arc<dyn Provider>>,
+}
+
+impl OperationRegistry {
+    /// a registry without any operation
+    pub fn empty() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+
+    /// a registry with all operations enabled by the features of `mio-core`
+    pub fn builtin() -> Self {
+        #[allow(unused_mut)]
+        let mut registry = Self::empty();
+        #[cfg(feature = "image")]
+        registry.register::<CropImage>().register::<ConvertImage>();
+        #[cfg(feature = "ocr")]
+        registry.register::<OcrText>();
+        #[cfg(feature = "video")]
+        registry.register::<FrameVideo>();
+        #[cfg(all(feature = "video", feature = "ocr"))]
+        registry.register::<OcrVideo>();
+        registry
+    }
+
+    /// register a single-base operable
+    pub fn register<T: Registrable>(&mut self) -> &mut Self {
+        self.provide(Registered::<T>(PhantomData))
+    }
+
+    /// register an arbitrary provider; later providers take precedence
+    pub fn provide(&mut self, provider: impl Provider + 'static) -> &mut Self {
+        self.providers.insert(0, Arc::new(provider));
+        self
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &dyn Provider> {
+        self.providers.iter().map(AsRef::as_ref)
+    }
+
+    /// find the provider of the operation upon base specters of the kinds
+    pub fn find(
+        &self,
+        kind: &OperationKind,
+        src: &[EntityKind],
+    ) -> Result<&dyn Provider, OperationError> {
+        self.providers()
+            .find(|provider| provider.kind() == *kind && provider.accepts(src))
+            .ok_or_else(|| OperationError::Unsupported {
+                kind: *kind,
+                src: src.to_vec(),
+            })
+    }
+}
+
+impl Default for OperationRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl std::fmt::Debug for OperationRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.providers().map(|provider| provider.name()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// an operation as a downstream crate would add it
+    #[derive(Serialize, Deserialize)]
+    struct Shout {
+        times: usize,
+    }
+
+    impl Registrable for Shout {
+        const NAME: &'static str = "Shout";
+        const ACCEPTS: &'static [EntityKind] = &[EntityKind::Text];
+        const EXT: EntityExt = EntityExt::Txt;
+        fn operation() -> OperationKind {
+            OperationKind::Custom(7)
+        }
+    }
+
+    impl Operable for Shout {
+        fn kind(&self) -> OperationKind {
+            OperationKind::Custom(7)
+        }
+        fn outputs(&self) -> Vec<(Role, EntityExt)> {
+            vec![(Role::Primary, EntityExt::Txt)]
+        }
+        fn execute(self, src: NamedTempFile) -> anyhow::Result<Vec<(Role, Vec<u8>)>> {
+            let text = fs::read_to_string(src.path())?.to_uppercase();
+            Ok(vec![(Role::Primary, text.repeat(self.times).into_bytes())])
+        }
+    }
+
+    #[test]
+    fn custom_operations_run_end_to_end() {
+        let (tmp, dirs) = MioDirs::temp();
+        let mut mio = Mio::with_dirs(dirs);
+        let kind = OperationKind::Custom(7);
+        assert!(kind.analyze(&mio.registry, [EntityKind::Text]).is_err());
+        mio.registry.register::<Shout>();
+        let analyzed = kind.analyze(&mio.registry, [EntityKind::Text]).unwrap();
+        assert_eq!(analyzed, EntityKind::Text);
+        assert_eq!(EntityKind::Text.synthesize(&mio.registry), vec![kind]);
+
+        let path = tmp.path().join("mio.txt");
+        fs::write(&path, "mio").unwrap();
+        let [base] = FileImport::new([&path]).interpret(&mut mio).unwrap()[..] else {
+            panic!("expected one entity")
+        };
+        let ring = MioInitiate::new(Shout { times: 2 }, vec![base])
+            .interpret(&mut mio)
+            .unwrap();
+        let (operation,) = ring.operations.values().collect_tuple().unwrap();
+        let output = operation.output(Role::Primary).unwrap();
+        let specter = mio.specterish(&output).unwrap();
+        specter.run(&mio).unwrap();
+        assert_eq!(specter.read(&mio.dirs).unwrap(), b"MIOMIO");
+    }
+}
@@ -30,8 +30,16 @@ impl Cipher {
         let key = Self::main_key();
         let cipher = Aes256Gcm::new(&key);
         let ciphertext = cipher
-            .encrypt(&nonce, plaintext.as_ref())
+            .encrypt(nonce, plaintext.as_ref())
             .map_err(|_| anyhow::anyhow!("encryption failed"))?;
         Ok(ciphertext)
     }
 }
+
+/// a fresh random secret in hex, e.g. to tell local clients from anyone else
+pub fn secret() -> String {
+    Aes256Gcm::generate_key(&mut OsRng)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
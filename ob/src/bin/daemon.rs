@@ -1,3 +1,190 @@
+use clipboard_master::{CallbackResult, ClipboardHandler, Master};
+use mio_core::{
+    ClipboardExclusion, ClipboardWatcher, Interpretable, Mio, PeriodicCapture, PeriodicCapturer,
+    ScreenFrames, ScreenRecorder, WatchedFolders,
+};
+#[cfg(feature = "audio")]
+use mio_core::{Microphone, VoiceRecorder};
+use mio_ob::{Request, Server};
+use notify::{RecursiveMode, Watcher};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// how often the daemon wakes up by itself, e.g. to rescan the watched folders
+/// for files that were still being written; only while there is something to look after
+const TICK: Duration = Duration::from_secs(5);
+
+/// what wakes the daemon up
+enum Signal {
+    Clipboard,
+    /// something changed in the watched folders
+    Folders,
+    Tick,
+    Request(Request),
+}
+
+struct ClipboardSignal(Sender<Signal>);
+
+impl ClipboardHandler for ClipboardSignal {
+    fn on_clipboard_change(&mut self) -> CallbackResult {
+        match self.0.send(Signal::Clipboard) {
+            Ok(()) => CallbackResult::Next,
+            Err(_) => CallbackResult::Stop,
+        }
+    }
+    fn on_clipboard_error(&mut self, error: io::Error) -> CallbackResult {
+        eprintln!("clipboard watcher: {}", error);
+        CallbackResult::Next
+    }
+}
+
 fn main() -> anyhow::Result<()> {
+    let mut mio = Mio::read_or_bak_with_default();
+    let (tx, rx) = mpsc::channel();
+
+    let mut clipboard = ClipboardWatcher::new(ClipboardExclusion::read(&mio.dirs)?)?;
+    {
+        let tx = tx.clone();
+        thread::spawn(move || Master::new(ClipboardSignal(tx)).run());
+    }
+
+    let folders = WatchedFolders::read(&mio.dirs).unwrap_or_else(|e| {
+        eprintln!("watched folders: {:#}, falling back to the default", e);
+        WatchedFolders::default()
+    });
+    let mut watcher = {
+        let tx = tx.clone();
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(_) => {
+                let _ = tx.send(Signal::Folders);
+            }
+            Err(e) => eprintln!("folder watcher: {}", e),
+        })?
+    };
+    for folder in folders.folders.iter() {
+        let mode = if folder.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        if let Err(e) = watcher.watch(&folder.path, mode) {
+            eprintln!("can't watch {}: {}", folder.path.display(), e);
+        }
+    }
+    let periodic = PeriodicCapture::read(&mio.dirs).unwrap_or_else(|e| {
+        eprintln!("periodic capture: {:#}, falling back to the default", e);
+        PeriodicCapture::default()
+    });
+    // folders and periodic captures always want ticks, a screen recording only while it runs
+    let always = !folders.folders.is_empty() || periodic.interval > 0;
+    let ticking = Arc::new(AtomicBool::new(always));
+    {
+        let tx = tx.clone();
+        let ticking = ticking.clone();
+        thread::spawn(move || loop {
+            // also catches up on whatever was dropped in while the daemon was down
+            if ticking.load(Ordering::Relaxed) && tx.send(Signal::Tick).is_err() {
+                break;
+            }
+            thread::sleep(TICK);
+        });
+    }
+
+    {
+        let server = Server::new(&mio.dirs)?;
+        let tx = tx.clone();
+        thread::spawn(move || server.run(|request| Ok(tx.send(Signal::Request(request))?)));
+    }
+
+    let mut periodic = PeriodicCapturer::new(periodic);
+    #[cfg(feature = "audio")]
+    let mut memo: Option<VoiceRecorder> = None;
+    let mut screen: Option<ScreenRecorder> = None;
+    for signal in rx {
+        let stop_screen = matches!(signal, Signal::Request(Request::StopScreenRecording));
+        // the ids registered upon the signal
+        let mut ids = Vec::new();
+        match signal {
+            Signal::Clipboard => match clipboard.poll(&mut mio) {
+                Ok(registered) => ids.extend(registered),
+                Err(e) => eprintln!("clipboard: {:#}", e),
+            },
+            Signal::Folders => {
+                ids.extend(folders.ingest(&mut mio));
+            }
+            Signal::Tick => {
+                ids.extend(folders.ingest(&mut mio));
+                match periodic.poll(&mut mio) {
+                    Ok(registered) => ids.extend(registered),
+                    Err(e) => eprintln!("periodic capture: {:#}", e),
+                }
+            }
+            #[cfg(feature = "audio")]
+            Signal::Request(Request::StartVoiceMemo { device }) => {
+                if memo.is_some() {
+                    eprintln!("voice memo: already recording");
+                } else {
+                    match Microphone::new(device.as_deref()) {
+                        Ok(input) => memo = Some(VoiceRecorder::start(input)),
+                        Err(e) => eprintln!("voice memo: {:#}", e),
+                    }
+                }
+            }
+            #[cfg(feature = "audio")]
+            Signal::Request(Request::StopVoiceMemo) => match memo.take() {
+                Some(recorder) => match recorder.stop().and_then(|memo| memo.interpret(&mut mio)) {
+                    Ok(registered) => ids.extend(registered),
+                    Err(e) => eprintln!("voice memo: {:#}", e),
+                },
+                None => eprintln!("voice memo: not recording"),
+            },
+            #[cfg(not(feature = "audio"))]
+            Signal::Request(Request::StartVoiceMemo { .. } | Request::StopVoiceMemo) => {
+                eprintln!("voice memo: built without the audio feature");
+            }
+            Signal::Request(Request::StartScreenRecording(options)) => {
+                if screen.is_some() {
+                    eprintln!("screen recording: already recording");
+                } else {
+                    match ScreenFrames::new(options.display) {
+                        Ok(source) => screen = Some(ScreenRecorder::start(source, &options)),
+                        Err(e) => eprintln!("screen recording: {:#}", e),
+                    }
+                }
+            }
+            Signal::Request(Request::StopScreenRecording) => {
+                if screen.is_none() {
+                    eprintln!("screen recording: not recording");
+                }
+            }
+        }
+        // a recording that reached its limit is registered as if stopped
+        if let Some(recorder) = screen.take_if(|recorder| stop_screen || recorder.is_finished()) {
+            match recorder
+                .stop()
+                .and_then(|recording| recording.interpret(&mut mio))
+            {
+                Ok(registered) => ids.extend(registered),
+                Err(e) => eprintln!("screen recording: {:#}", e),
+            }
+        }
+        ticking.store(always || screen.is_some(), Ordering::Relaxed);
+        let settled = mio.settle(false);
+        // the index is only rewritten when something changed
+        if ids.is_empty() && settled.is_empty() {
+            continue;
+        }
+        if let Err(e) = mio.flush() {
+            eprintln!("flush: {:#}", e);
+        }
+    }
     Ok(())
 }
@@ -1,6 +1,6 @@
 use mio_core::{
     Clipboard, CropImage, EntityExt, Interpretable, Mio, MioArchive, MioForce, MioInitiate,
-    OcrText, ScreenShot, MioPurge,
+    MioPurge, OcrText, ScreenShot,
 };
 
 fn main() -> anyhow::Result<()> {
@@ -8,7 +8,7 @@ fn main() -> anyhow::Result<()> {
     let mut mio = Mio::read_or_bak_with_default();
 
     // screenshot
-    let ids = ScreenShot.interpret(&mut mio)?;
+    let ids = ScreenShot::All.interpret(&mut mio)?;
     let del_ids = ids.clone();
     // crop
     let diff = MioInitiate::new(
@@ -23,19 +23,32 @@ fn main() -> anyhow::Result<()> {
     )
     .interpret(&mut mio)?;
     let ids = diff.specters.keys().copied().collect();
-    let () = MioForce { ids }.interpret(&mio)?;
-    // ocr
-    let ids = diff.specters.keys().copied().collect();
+    let forced = MioForce { ids }.interpret(&mut mio)?;
+    // ocr, upon the crops that actualized
+    let ids = forced
+        .into_iter()
+        .filter_map(|(id, res)| match res {
+            Ok(()) => Some(id),
+            Err(e) => {
+                eprintln!("failed to crop {}: {:#}", id.stem(), e);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    if ids.is_empty() {
+        anyhow::bail!("no crop is actualized");
+    }
     let diff = MioInitiate::new(
         OcrText {
-            ext: EntityExt::Png,
+            ext: EntityExt::Txt,
             lang: "eng".to_string(),
+            ..Default::default()
         },
         ids,
     )
     .interpret(&mut mio)?;
     let ids = diff.specters.keys().copied().collect();
-    let () = MioForce { ids }.interpret(&mio)?;
+    let _forced = MioForce { ids }.interpret(&mut mio)?;
 
     // clipboard
     let clipboard = Clipboard::new()?;
@@ -53,6 +66,7 @@ fn main() -> anyhow::Result<()> {
     MioPurge.interpret(&mut mio)?;
 
     // save
+    let _settled = mio.settle(true);
     mio.flush()?;
     eprintln!("{:#?}", mio);
     Ok(())
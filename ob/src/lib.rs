@@ -1,27 +1,125 @@
-use mio_core::Mio;
-use std::{io::Read, net::TcpListener};
+use mio_core::{MioDirs, RecordingOptions};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+/// what the daemon is asked to do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Request {
+    /// start recording a voice memo from the input device, or the default one
+    StartVoiceMemo {
+        device: Option<String>,
+    },
+    /// stop recording and register the voice memo
+    StopVoiceMemo,
+    StartScreenRecording(RecordingOptions),
+    /// stop recording and register the screen recording
+    StopScreenRecording,
+}
+
+/// a request along with the token that proves the client may read the user's files
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    token: String,
+    request: Request,
+}
+
+/// how long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+/// no request comes anywhere near this
+const MAX_REQUEST: u64 = 64 * 1024;
 
 pub struct Server {
     listener: TcpListener,
-    mio: Mio,
+    token: String,
 }
 pub struct Client;
 
+/// compare the tokens in a time that doesn't tell how much of them matches
+fn same_token(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let diff = (a.iter().zip(b)).fold(0, |diff, (x, y)| diff | (x ^ y));
+    a.len() == b.len() && std::hint::black_box(diff) == 0
+}
+
 impl Server {
     pub fn port() -> u16 {
         9720
     }
-    pub fn new() -> anyhow::Result<Self> {
+    /// where the token of the running server is kept, readable by the user only
+    pub fn token_path(dirs: &MioDirs) -> PathBuf {
+        dirs.config_dir.join("miod.token")
+    }
+    pub fn new(dirs: &MioDirs) -> anyhow::Result<Self> {
         let listener = TcpListener::bind(("127.0.0.1", Self::port()))?;
-
-        let mio = Mio::read_or_bak_with_default();
-        Ok(Self { listener, mio })
+        let token = mio_core::secret();
+        let path = Self::token_path(dirs);
+        let _ = fs::remove_file(&path);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(&path)?.write_all(token.as_bytes())?;
+        Ok(Self { listener, token })
+    }
+    /// read the request of a connection, if it's well-formed and carries the token
+    fn read(mut stream: TcpStream, token: &str) -> anyhow::Result<Request> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut buffer = Vec::new();
+        (&mut stream).take(MAX_REQUEST).read_to_end(&mut buffer)?;
+        let envelope: Envelope = serde_json::from_slice(&buffer)?;
+        if !same_token(&envelope.token, token) {
+            anyhow::bail!("wrong token")
+        }
+        Ok(envelope.request)
     }
-    pub fn run(&self) -> anyhow::Result<()> {
-        loop {
-            let (mut stream, addr) = self.listener.accept()?;
-            let mut buffer = [0; 1024];
-            stream.read(&mut buffer)?;
+    /// accept requests and hand them over in order, until handing over fails;
+    /// each connection is read on its own, so that a stalled client holds up no other
+    pub fn run(&self, mut handle: impl FnMut(Request) -> anyhow::Result<()>) -> anyhow::Result<()> {
+        let (tx, rx) = mpsc::channel();
+        let (listener, token) = (self.listener.try_clone()?, self.token.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        eprintln!("can't accept connection: {}", e);
+                        continue;
+                    }
+                };
+                let (tx, token) = (tx.clone(), token.clone());
+                thread::spawn(move || match Self::read(stream, &token) {
+                    Ok(request) => {
+                        let _ = tx.send(request);
+                    }
+                    Err(e) => eprintln!("bad request: {:#}", e),
+                });
+            }
+        });
+        for request in rx {
+            handle(request)?;
         }
+        Ok(())
+    }
+}
+
+impl Client {
+    pub fn send(dirs: &MioDirs, request: &Request) -> anyhow::Result<()> {
+        let token = fs::read_to_string(Server::token_path(dirs))?;
+        let envelope = Envelope {
+            token: token.trim().to_string(),
+            request: request.clone(),
+        };
+        let mut stream = TcpStream::connect(("127.0.0.1", Server::port()))?;
+        stream.write_all(&serde_json::to_vec(&envelope)?)?;
+        stream.shutdown(Shutdown::Write)?;
+        Ok(())
     }
 }
@@ -55,12 +55,13 @@ fn Home(cx: Scope) -> Element {
 
     render! {
         ul {
-            for eph in view.timeline.iter() {
+            for eph in view.distinct() {
                 li {
                     "{format_time(eph.time)}"
                     div {
                         SpecterEntry {
                             dirs: &mio.dirs,
+                            registry: &mio.registry,
                             id: eph.base,
                             ring: &view.ring,
                         }
@@ -72,16 +73,34 @@ fn Home(cx: Scope) -> Element {
 }
 
 #[inline_props]
-fn SpecterEntry<'a>(cx: Scope, dirs: &'a MioDirs, id: MioId, ring: &'a MioRing) -> Element {
-    let specter = ring.specterish(&id);
-    let specter_file = specter.read_as_temp(dirs).unwrap();
-    let path = TempAssets::persistize(specter_file.path());
+fn SpecterEntry<'a>(
+    cx: Scope,
+    dirs: &'a MioDirs,
+    registry: &'a OperationRegistry,
+    id: MioId,
+    ring: &'a MioRing,
+) -> Element {
+    let Ok(specter) = ring.specterish(&id) else {
+        return render!(div { "{id.stem()} is gone" });
+    };
     let kind = specter.kind();
-    let ops = kind.synthesize();
+    let ops = kind.synthesize(registry);
     let ops_group = rsx!(OperationButtonGroup { ops: ops });
+    // lazy specters have no file until they are actualized
+    if !specter.exists(dirs) {
+        return render!(div {
+            style: "border: 1px black dashed; padding: 5px;",
+            "{specter.extension()}, not actualized yet"
+            ops_group
+        });
+    }
+    let path = match specter.read_as_temp(dirs) {
+        Ok(specter_file) => TempAssets::persistize(specter_file.path()),
+        Err(e) => return render!(div { "can't read {id.stem()}: {e:#}" }),
+    };
     match kind {
         EntityKind::Text => {
-            let text = std::fs::read_to_string(&path).unwrap();
+            let text = std::fs::read_to_string(&path).unwrap_or_default();
             render!(div {
                 style: "border: 1px black solid; padding: 5px;",
                 pre {
@@ -98,8 +117,23 @@ fn SpecterEntry<'a>(cx: Scope, dirs: &'a MioDirs, id: MioId, ring: &'a MioRing)
             }
             ops_group
         }),
-        EntityKind::Audio => todo!(),
-        EntityKind::Video => todo!(),
+        EntityKind::Audio => render!(div {
+            style: "border: 1px black solid; padding: 5px;",
+            audio {
+                controls: true,
+                src: "{path.display()}",
+            }
+            ops_group
+        }),
+        EntityKind::Video => render!(div {
+            style: "border: 1px black solid; padding: 5px;",
+            video {
+                style: "max-height: 120px;",
+                controls: true,
+                src: "{path.display()}",
+            }
+            ops_group
+        }),
     }
 }
 